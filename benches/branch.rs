@@ -0,0 +1,165 @@
+//! Generates large acyclic graphs, shaped like those of generated shaders, and times
+//! how long [`Branch`] takes to restructure them. Branches either meet again where they
+//! are post-dominated, or leave their arms to many merges in a row, which needs many
+//! continuations. The time per node should stay about the same as the graphs grow, so it
+//! is printed along with how much it grew since the smallest graph.
+
+use std::time::{Duration, Instant};
+
+use flow_structurer::{
+	branch::Branch,
+	list::{List, Statement},
+	set::Set,
+	view::View,
+};
+
+const SIZES: [usize; 4] = [1_000, 5_000, 20_000, 50_000];
+const DEPTH: usize = 6;
+
+#[derive(Clone, Copy, Debug)]
+enum Shape {
+	Nested,
+	Continuations,
+}
+
+struct Generator {
+	list: List<()>,
+	state: u64,
+	shape: Shape,
+}
+
+impl Generator {
+	fn new(seed: u64, shape: Shape) -> Self {
+		Self {
+			list: List::new(),
+			state: seed,
+			shape,
+		}
+	}
+
+	fn next_below(&mut self, bound: usize) -> usize {
+		self.state ^= self.state << 13;
+		self.state ^= self.state >> 7;
+		self.state ^= self.state << 17;
+
+		(self.state % bound as u64) as usize
+	}
+
+	fn add_simple(&mut self) -> usize {
+		self.list.add_statement(Statement::Simple(()))
+	}
+
+	// Arms may be empty, going straight from the head to where they meet.
+	fn add_branch(&mut self, head: usize, depth: usize) -> usize {
+		let merge = self.add_simple();
+
+		for _ in 0..2 + self.next_below(3) {
+			if self.next_below(4) == 0 {
+				self.list.add_edge(head, merge);
+			} else {
+				let (start, end) = self.add_region(depth - 1);
+
+				self.list.add_edge(head, start);
+				self.list.add_edge(end, merge);
+			}
+		}
+
+		merge
+	}
+
+	// Arms leave to any of the merges, which follow each other, so the branch
+	// has a continuation for each merge reached.
+	fn add_escapes(&mut self, head: usize, depth: usize) -> usize {
+		let merges: Vec<usize> = (0..2 + self.next_below(3))
+			.map(|_| self.add_simple())
+			.collect();
+
+		for pair in merges.windows(2) {
+			self.list.add_edge(pair[0], pair[1]);
+		}
+
+		// Every merge is reached by an arm, and the rest are chosen at random.
+		for index in 0..merges.len() + self.next_below(3) {
+			let (start, end) = self.add_region(depth - 1);
+			let merge = merges
+				.get(index)
+				.copied()
+				.unwrap_or_else(|| merges[self.next_below(merges.len())]);
+
+			self.list.add_edge(head, start);
+			self.list.add_edge(end, merge);
+		}
+
+		merges[merges.len() - 1]
+	}
+
+	fn add_region(&mut self, depth: usize) -> (usize, usize) {
+		let start = self.add_simple();
+		let mut end = start;
+
+		for _ in 0..1 + self.next_below(4) {
+			if depth != 0 && self.next_below(3) == 0 {
+				end = if matches!(self.shape, Shape::Continuations) && self.next_below(2) == 0 {
+					self.add_escapes(end, depth)
+				} else {
+					self.add_branch(end, depth)
+				};
+			} else {
+				let next = self.add_simple();
+
+				self.list.add_edge(end, next);
+
+				end = next;
+			}
+		}
+
+		(start, end)
+	}
+
+	// Regions follow each other, so that most nodes come after many branches.
+	fn add_sequence(&mut self, len: usize) -> usize {
+		let start = self.add_simple();
+		let mut end = start;
+
+		while self.list.len() < len {
+			let (first, last) = self.add_region(DEPTH);
+
+			self.list.add_edge(end, first);
+
+			end = last;
+		}
+
+		start
+	}
+}
+
+fn time_branch(len: usize, shape: Shape) -> (usize, Duration) {
+	let mut generator = Generator::new(0x5EED ^ len as u64, shape);
+	let start = generator.add_sequence(len);
+	let mut list = generator.list;
+	let mut set: Set = (0..list.len()).collect();
+
+	let now = Instant::now();
+
+	Branch::new().run(&mut list, &mut set, start);
+
+	(set.len(), now.elapsed())
+}
+
+fn main() {
+	for shape in [Shape::Nested, Shape::Continuations] {
+		println!("{shape:?}");
+
+		let mut first = None;
+
+		for len in SIZES {
+			let (nodes, elapsed) = time_branch(len, shape);
+			let per_node = elapsed.as_secs_f64() * 1e9 / nodes as f64;
+			let growth = per_node / *first.get_or_insert(per_node);
+
+			println!(
+				"{nodes:>8} nodes in {elapsed:>12.3?}, {per_node:>8.1} ns per node, {growth:>5.2}x"
+			);
+		}
+	}
+}
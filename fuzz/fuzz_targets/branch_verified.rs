@@ -0,0 +1,27 @@
+#![no_main]
+
+use flow_structurer::{branch::Branch, verify::Verifier};
+use libfuzzer_sys::fuzz_target;
+
+use crate::sample::arbitrary::DirectedAcyclicGraph;
+
+mod sample;
+
+fuzz_target!(|built: DirectedAcyclicGraph| {
+	let mut list = built.into_inner();
+	let mut set = (0..list.len()).collect();
+
+	let result = Branch::new().try_run(&mut list, &mut set, 0);
+
+	assert!(result.is_ok(), "`Branch` rejected an acyclic graph");
+
+	let result = Verifier::new().run(&list, set.as_slice(), 0);
+
+	assert_eq!(result, Ok(()), "`Branch` left regions unstructured");
+
+	let len = set.len();
+
+	Branch::new().run(&mut list, &mut set, 0);
+
+	assert_eq!(len, set.len(), "`Branch` ran twice");
+});
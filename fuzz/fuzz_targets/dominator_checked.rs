@@ -0,0 +1,59 @@
+#![no_main]
+
+use flow_structurer::{
+	pass::{dominator_finder::DominatorFinder, inverted::Inverted},
+	set::Set,
+	view::{Predecessors, Successors},
+};
+use libfuzzer_sys::fuzz_target;
+
+use crate::sample::arbitrary::DirectedGraph;
+
+mod sample;
+
+fn find_reached<N: Successors>(view: &N, start: usize, removed: usize) -> Set {
+	let mut reached = Set::new();
+	let mut stack = vec![start];
+
+	while let Some(id) = stack.pop() {
+		if id == removed || reached.contains(id) {
+			continue;
+		}
+
+		reached.grow_insert(id);
+		stack.extend(view.successors(id));
+	}
+
+	reached
+}
+
+// A node dominates another if removing it makes the other unreachable.
+fn check_dominators<N: Predecessors + Successors>(view: &N, len: usize, start: usize) {
+	let set: Set = (0..len).collect();
+	let reached = find_reached(view, start, usize::MAX);
+	let mut finder = DominatorFinder::new();
+
+	finder.run(view, set.as_slice(), start);
+
+	for dominator in 0..len {
+		let without = find_reached(view, start, dominator);
+
+		for id in 0..len {
+			let expected = reached.contains(id)
+				&& (id == dominator || reached.contains(dominator) && !without.contains(id));
+
+			assert_eq!(finder.dominates(dominator, id), expected, "wrong dominator");
+		}
+	}
+}
+
+fuzz_target!(|built: DirectedGraph| {
+	let (list, start) = built.into_inner();
+	let mut exits = (0..list.len()).filter(|&id| list.successors(id).next().is_none());
+
+	check_dominators(&list, list.len(), start);
+
+	if let (Some(exit), None) = (exits.next(), exits.next()) {
+		check_dominators(&Inverted(&list), list.len(), exit);
+	}
+});
@@ -0,0 +1,41 @@
+#![no_main]
+
+use flow_structurer::{
+	branch::Branch, equivalence::Equivalence, pass::exit_unifier::ExitUnifier, repeat::Repeat,
+	verify::Verifier,
+};
+use libfuzzer_sys::fuzz_target;
+
+use crate::sample::arbitrary::DirectedGraph;
+
+mod sample;
+
+const BUDGET: usize = 16;
+
+fuzz_target!(|built: DirectedGraph| {
+	let (mut list, start) = built.into_inner();
+	let original = list.clone();
+	let mut set = (0..list.len()).collect();
+	let mut repeat = Repeat::new();
+	let mut branch = Branch::new();
+
+	repeat.set_budget(BUDGET);
+	repeat.run(&mut list, &mut set);
+
+	ExitUnifier::new().run(&mut list, &mut set);
+
+	branch.set_budget(BUDGET);
+	branch.run(&mut list, &mut set, start);
+
+	let result = Verifier::new().run(&list, set.as_slice(), start);
+
+	assert_eq!(
+		result,
+		Ok(()),
+		"`Repeat` and `Branch` left the graph unstructured"
+	);
+
+	let result = Equivalence::new().run(&original, start, &list, start);
+
+	assert_eq!(result, Ok(()), "duplicating nodes changed the program");
+});
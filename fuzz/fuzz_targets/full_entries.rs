@@ -0,0 +1,36 @@
+#![no_main]
+
+use flow_structurer::{
+	branch::Branch,
+	pass::{entry_unifier::EntryUnifier, exit_unifier::ExitUnifier},
+	repeat::Repeat,
+	verify::Verifier,
+};
+use libfuzzer_sys::fuzz_target;
+
+use crate::sample::arbitrary::DirectedGraphWithEntries;
+
+mod sample;
+
+fuzz_target!(|built: DirectedGraphWithEntries| {
+	let mut list = built.into_inner();
+	let mut set = (0..list.len()).collect();
+
+	let Some(start) = EntryUnifier::new().run(&mut list, &mut set) else {
+		return;
+	};
+
+	Repeat::new().run(&mut list, &mut set);
+
+	ExitUnifier::new().run(&mut list, &mut set);
+
+	Branch::new().run(&mut list, &mut set, start);
+
+	let result = Verifier::new().run(&list, set.as_slice(), start);
+
+	assert_eq!(
+		result,
+		Ok(()),
+		"`Repeat` and `Branch` left regions unstructured"
+	);
+});
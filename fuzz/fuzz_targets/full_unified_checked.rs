@@ -0,0 +1,48 @@
+#![no_main]
+
+use flow_structurer::{
+	branch::Branch, pass::exit_unifier::ExitUnifier, repeat::Repeat, verify::Verifier,
+	view::Successors,
+};
+use libfuzzer_sys::fuzz_target;
+
+use crate::sample::arbitrary::DirectedGraph;
+
+mod sample;
+
+fuzz_target!(|built: DirectedGraph| {
+	let (mut list, start) = built.into_inner();
+	let mut set = (0..list.len()).collect();
+
+	Repeat::new().run(&mut list, &mut set);
+
+	ExitUnifier::new().run(&mut list, &mut set);
+
+	let summary = Branch::new().run(&mut list, &mut set, start);
+
+	let is_endless = set
+		.ascending()
+		.all(|id| list.successors(id).next().is_some());
+
+	assert!(
+		summary.exit.is_some() || is_endless,
+		"`Branch` left many exits"
+	);
+
+	let result = Verifier::new().run(&list, set.as_slice(), start);
+
+	assert_eq!(
+		result,
+		Ok(()),
+		"`Repeat` and `Branch` left regions unstructured"
+	);
+
+	let len = set.len();
+	let summary = Repeat::new().run(&mut list, &mut set);
+
+	assert!(!summary.changed && len == set.len(), "`Repeat` ran twice");
+
+	let summary = Branch::new().run(&mut list, &mut set, start);
+
+	assert!(!summary.changed && len == set.len(), "`Branch` ran twice");
+});
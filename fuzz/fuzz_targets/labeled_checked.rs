@@ -0,0 +1,76 @@
+#![no_main]
+
+use flow_structurer::labeled::{Builder, Node};
+use libfuzzer_sys::fuzz_target;
+
+use crate::sample::{arbitrary::DirectedGraph, lowered::Lowering};
+
+mod sample;
+
+// Blocks are labeled by what follows them and loops by a new node entering them,
+// so that breaks go straight to their targets.
+impl Lowering<'_> {
+	fn add_node(&mut self, node: &Node, next: usize) -> usize {
+		match node {
+			&Node::Simple(id) => self.add_simple(id, next),
+			Node::Sequence(list) => list
+				.iter()
+				.rev()
+				.fold(next, |next, node| self.add_node(node, next)),
+			Node::Select { head, arms } => {
+				let lowered = self.add_copy(*head);
+
+				for arm in arms {
+					let arm = self.add_node(arm, next);
+
+					self.lowered.add_edge(lowered, arm);
+				}
+
+				lowered
+			}
+			Node::Block(body) => {
+				self.labels.push((next, next));
+
+				let body = self.add_node(body, next);
+
+				self.labels.pop();
+
+				body
+			}
+			Node::Loop(body) => {
+				let entry = self.lowered.add_no_operation();
+
+				self.labels.push((entry, entry));
+
+				let body = self.add_node(body, self.end);
+
+				self.labels.pop();
+				self.lowered.add_edge(entry, body);
+
+				entry
+			}
+			&Node::Break(depth) => self.find_label(depth).1,
+			Node::Return => self.end,
+		}
+	}
+}
+
+fuzz_target!(|built: DirectedGraph| {
+	let (mut list, start) = built.into_inner();
+	let original = list.clone();
+	let mut set = (0..list.len()).collect();
+
+	let tree = Builder::new()
+		.run(&mut list, &mut set, start)
+		.expect("an edge left the set");
+
+	let mut lowering = Lowering::new(&list);
+	let entry = lowering.add_node(&tree, lowering.end);
+	let result = lowering.check(&original, start, entry);
+
+	assert_eq!(
+		result,
+		Ok(()),
+		"the labeled tree does not run as the graph does"
+	);
+});
@@ -0,0 +1,115 @@
+#![no_main]
+
+use flow_structurer::{
+	list::List,
+	pass::loop_nesting_finder::{LoopNestingFinder, Nest},
+	set::Set,
+	view::{Predecessors, Successors},
+};
+use libfuzzer_sys::fuzz_target;
+
+use crate::sample::arbitrary::DirectedGraph;
+
+mod sample;
+
+fn find_reachable(list: &List<()>, set: &Set, start: usize) -> Set {
+	let mut reachable = Set::new();
+	let mut stack: Vec<_> = list
+		.successors(start)
+		.filter(|&id| set.contains(id))
+		.collect();
+
+	while let Some(id) = stack.pop() {
+		if reachable.contains(id) {
+			continue;
+		}
+
+		reachable.grow_insert(id);
+		stack.extend(list.successors(id).filter(|&id| set.contains(id)));
+	}
+
+	reachable
+}
+
+fn assert_nest(list: &List<()>, nest: &Nest) {
+	for id in nest.nodes.ascending() {
+		let reachable = find_reachable(list, &nest.nodes, id);
+
+		assert!(
+			nest.nodes.ascending().all(|id| reachable.contains(id)),
+			"`LoopNestingFinder` found a region that is not strongly connected"
+		);
+
+		let entered = list.predecessors(id).any(|id| !nest.nodes.contains(id));
+
+		assert_eq!(
+			entered,
+			nest.entries.contains(&id),
+			"`LoopNestingFinder` found the wrong entries"
+		);
+	}
+}
+
+// The children must cover every node left repeating once the entries are removed.
+fn assert_children(list: &List<()>, nests: &[Nest], set: &Set, children: &[usize]) {
+	let mut covered = Set::new();
+
+	for &child in children {
+		for id in nests[child].nodes.ascending() {
+			assert!(
+				set.contains(id),
+				"`LoopNestingFinder` found a region outside its parent"
+			);
+			assert!(
+				!covered.contains(id),
+				"`LoopNestingFinder` found overlapping regions"
+			);
+
+			covered.grow_insert(id);
+		}
+	}
+
+	for id in set.ascending().filter(|&id| !covered.contains(id)) {
+		assert!(
+			!find_reachable(list, set, id).contains(id),
+			"`LoopNestingFinder` missed a region"
+		);
+	}
+}
+
+fuzz_target!(|built: DirectedGraph| {
+	let (list, _) = built.into_inner();
+	let set: Set = (0..list.len()).collect();
+	let mut finder = LoopNestingFinder::new();
+
+	finder.run(&list, set.as_slice(), None);
+
+	let nests = finder.nests();
+
+	assert_children(&list, nests, &set, finder.roots());
+
+	for (index, nest) in nests.iter().enumerate() {
+		assert_nest(&list, nest);
+
+		if nest.entries.is_empty() {
+			assert!(
+				nest.children.is_empty(),
+				"`LoopNestingFinder` nested in an unentered region"
+			);
+
+			continue;
+		}
+
+		let mut inner = nest.nodes.clone();
+
+		for &entry in &nest.entries {
+			inner.remove(entry);
+		}
+
+		assert!(nest
+			.children
+			.iter()
+			.all(|&child| nests[child].parent == Some(index)));
+		assert_children(&list, nests, &inner, &nest.children);
+	}
+});
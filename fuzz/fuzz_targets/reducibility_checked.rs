@@ -0,0 +1,80 @@
+#![no_main]
+
+use flow_structurer::{
+	pass::{dominator_finder::DominatorFinder, reducibility_finder::ReducibilityFinder},
+	repeat::Repeat,
+	set::Set,
+	view::Successors,
+};
+use libfuzzer_sys::fuzz_target;
+
+use crate::sample::arbitrary::DirectedGraphWithStart;
+
+mod sample;
+
+fuzz_target!(|built: DirectedGraphWithStart| {
+	let (mut list, start) = built.into_inner();
+	let mut set: Set = (0..list.len()).collect();
+	let mut finder = ReducibilityFinder::new();
+	let mut dominator_finder = DominatorFinder::new();
+
+	finder.run(&list, set.as_slice(), start);
+	dominator_finder.run(&list, set.as_slice(), start);
+
+	for region in finder.regions() {
+		let reachable = region
+			.nodes
+			.ascending()
+			.any(|id| id == start || dominator_finder.immediate(id).is_some());
+
+		assert_eq!(
+			region.reachable, reachable,
+			"`ReducibilityFinder` found the wrong regions reachable"
+		);
+
+		assert!(
+			region.reachable || !region.irreducible,
+			"`ReducibilityFinder` found an unreachable region irreducible"
+		);
+
+		let is_dominated = region.entries.iter().any(|entry| {
+			region
+				.nodes
+				.ascending()
+				.all(|id| dominator_finder.dominates(entry.id, id))
+		});
+
+		assert_eq!(
+			region.irreducible,
+			region.reachable && !is_dominated,
+			"`ReducibilityFinder` disagrees with the dominators on a region"
+		);
+
+		for entry in &region.entries {
+			assert!(
+				entry.id == start || !entry.predecessors.is_empty(),
+				"`ReducibilityFinder` found an entry not entered"
+			);
+			assert!(
+				entry
+					.predecessors
+					.iter()
+					.all(|&id| list.successors(id).any(|id| id == entry.id)),
+				"`ReducibilityFinder` found the wrong predecessors"
+			);
+		}
+	}
+
+	let irreducible = finder.irreducible().count();
+	let mut repeat = Repeat::new();
+
+	repeat.set_start(Some(start));
+
+	let summary = repeat.run(&mut list, &mut set);
+
+	assert!(
+		irreducible <= summary.irreducible,
+		"`ReducibilityFinder` found regions irreducible that `Repeat` did not"
+	);
+	assert_eq!(finder.is_reducible(), irreducible == 0);
+});
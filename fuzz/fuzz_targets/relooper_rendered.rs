@@ -0,0 +1,230 @@
+#![no_main]
+
+use flow_structurer::{
+	relooper::{Condition, Relooper, Render},
+	view::{Flag, Kind, Successors},
+};
+use libfuzzer_sys::fuzz_target;
+
+use crate::sample::{arbitrary::DirectedGraph, lowered::Lowering};
+
+mod sample;
+
+#[derive(Clone, Copy)]
+enum Arm {
+	Branch(Option<usize>),
+	Flag(Flag, usize),
+}
+
+enum Code {
+	Block(usize),
+	Assignment(Flag, usize),
+	Selection(Vec<(Arm, Vec<Code>)>),
+	Loop(Vec<Code>),
+	Break(usize),
+	Continue(usize),
+}
+
+// The rendered code is recorded as it is written, with the bodies of the
+// selections and loops being written kept on a stack.
+struct Recorder {
+	bodies: Vec<Vec<Code>>,
+	selections: Vec<Vec<(Arm, Vec<Code>)>>,
+	arms: Vec<Arm>,
+}
+
+impl Recorder {
+	fn push(&mut self, code: Code) {
+		self.bodies.last_mut().unwrap().push(code);
+	}
+}
+
+impl Render<usize, usize> for Recorder {
+	fn block(&mut self, code: &usize) {
+		self.push(Code::Block(*code));
+	}
+
+	fn assignment(&mut self, flag: Flag, value: usize) {
+		self.push(Code::Assignment(flag, value));
+	}
+
+	fn enter_selection(&mut self) {
+		self.selections.push(Vec::new());
+	}
+
+	fn leave_selection(&mut self) {
+		let selection = self.selections.pop().unwrap();
+
+		self.push(Code::Selection(selection));
+	}
+
+	fn enter_arm(&mut self, condition: Condition<'_, usize>) {
+		let arm = match condition {
+			Condition::Branch(condition) => Arm::Branch(condition.copied()),
+			Condition::Flag(flag, value) => Arm::Flag(flag, value),
+		};
+
+		self.arms.push(arm);
+		self.bodies.push(Vec::new());
+	}
+
+	fn leave_arm(&mut self) {
+		let body = self.bodies.pop().unwrap();
+		let arm = self.arms.pop().unwrap();
+
+		self.selections.last_mut().unwrap().push((arm, body));
+	}
+
+	fn enter_loop(&mut self) {
+		self.bodies.push(Vec::new());
+	}
+
+	fn leave_loop(&mut self) {
+		let body = self.bodies.pop().unwrap();
+
+		self.push(Code::Loop(body));
+	}
+
+	fn break_loop(&mut self, depth: usize) {
+		self.push(Code::Break(depth));
+	}
+
+	fn continue_loop(&mut self, depth: usize) {
+		self.push(Code::Continue(depth));
+	}
+}
+
+// Selections on branches take the successor of the block before them, while those
+// on flags get a node of their own. Loops repeat when their body falls through.
+impl Lowering<'_> {
+	fn add_arms(&mut self, arms: &[(Arm, Vec<Code>)], next: usize) -> Vec<usize> {
+		let mut entries = vec![usize::MAX; arms.len()];
+
+		for (arm, body) in arms {
+			let index = match *arm {
+				Arm::Branch(condition) => condition.unwrap_or(arms.len() - 1),
+				Arm::Flag(_, value) => value,
+			};
+
+			entries[index] = self.add_sequence(body, next);
+		}
+
+		assert!(
+			!entries.contains(&usize::MAX),
+			"a selection has arms missing"
+		);
+
+		entries
+	}
+
+	fn add_sequence(&mut self, list: &[Code], mut next: usize) -> usize {
+		let mut successors = None;
+
+		for code in list.iter().rev() {
+			next = match code {
+				&Code::Block(block) => {
+					let lowered = self.add_copy(block);
+
+					for successor in successors.take().unwrap_or_else(|| vec![next]) {
+						self.lowered.add_edge(lowered, successor);
+					}
+
+					lowered
+				}
+				&Code::Assignment(flag, value) => {
+					let lowered = self
+						.lowered
+						.add_node(Kind::Assignment(flag, value), usize::MAX);
+
+					self.lowered.add_edge(lowered, next);
+
+					lowered
+				}
+				Code::Selection(arms) => match arms.first() {
+					Some(&(Arm::Flag(flag, _), _)) => {
+						let lowered = self.lowered.add_node(Kind::Selection(flag), usize::MAX);
+
+						for successor in self.add_arms(arms, next) {
+							self.lowered.add_edge(lowered, successor);
+						}
+
+						lowered
+					}
+					_ => {
+						successors = Some(self.add_arms(arms, next));
+
+						continue;
+					}
+				},
+				Code::Loop(body) => {
+					let entry = self.lowered.add_no_operation();
+
+					self.labels.push((entry, next));
+
+					let body = self.add_sequence(body, entry);
+
+					self.labels.pop();
+					self.lowered.add_edge(entry, body);
+
+					entry
+				}
+				&Code::Break(depth) => self.find_label(depth).1,
+				&Code::Continue(depth) => self.find_label(depth).0,
+			};
+
+			assert!(
+				successors.is_none(),
+				"a selection on branches does not follow a block"
+			);
+		}
+
+		assert!(
+			successors.is_none(),
+			"a selection on branches does not follow a block"
+		);
+
+		next
+	}
+}
+
+fuzz_target!(|built: DirectedGraph| {
+	let (list, start) = built.into_inner();
+	let mut relooper = Relooper::new();
+
+	for id in 0..list.len() {
+		relooper.add_block(id);
+	}
+
+	// The last branch of each block is taken by default, keeping the order of successors.
+	for id in 0..list.len() {
+		let count = list.successors(id).count();
+
+		for (index, successor) in list.successors(id).enumerate() {
+			relooper.add_branch(id, successor, (index + 1 != count).then_some(index));
+		}
+	}
+
+	let mut recorder = Recorder {
+		bodies: vec![Vec::new()],
+		selections: Vec::new(),
+		arms: Vec::new(),
+	};
+
+	relooper
+		.render(start, &mut recorder)
+		.expect("the structured graph could not be rendered");
+
+	let code = recorder.bodies.pop().unwrap();
+
+	assert!(recorder.bodies.is_empty(), "a loop or arm was left open");
+
+	let mut lowering = Lowering::new(&list);
+	let entry = lowering.add_sequence(&code, lowering.end);
+	let result = lowering.check(&list, start, entry);
+
+	assert_eq!(
+		result,
+		Ok(()),
+		"the rendered code does not run as the graph does"
+	);
+});
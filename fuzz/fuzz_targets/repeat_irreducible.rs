@@ -0,0 +1,36 @@
+#![no_main]
+
+use flow_structurer::{equivalence::Equivalence, repeat::Repeat};
+use libfuzzer_sys::fuzz_target;
+
+use crate::sample::arbitrary::DirectedGraph;
+
+mod sample;
+
+fuzz_target!(|built: DirectedGraph| {
+	let (mut list, start) = built.into_inner();
+	let original = list.clone();
+	let mut set = (0..list.len()).collect();
+	let mut repeat = Repeat::new();
+
+	repeat.set_irreducible_only(true);
+
+	let summary = repeat.run(&mut list, &mut set);
+
+	assert!(
+		summary.changed || summary.irreducible == 0,
+		"`Repeat` left repetitions with many entries"
+	);
+
+	let result = Equivalence::new().run(&original, start, &list, start);
+
+	assert_eq!(result, Ok(()), "`Repeat` changed the program");
+
+	let summary = repeat.run(&mut list, &mut set);
+
+	assert_eq!(
+		summary.irreducible, 0,
+		"`Repeat` left repetitions with many entries"
+	);
+	assert!(!summary.changed, "`Repeat` ran twice");
+});
@@ -0,0 +1,43 @@
+#![no_main]
+
+use flow_structurer::{equivalence::Equivalence, repeat::Repeat, view::Successors};
+use libfuzzer_sys::fuzz_target;
+
+use crate::sample::arbitrary::DirectedGraphWithStart;
+
+mod sample;
+
+fuzz_target!(|built: DirectedGraphWithStart| {
+	let (mut list, start) = built.into_inner();
+	let original = list.clone();
+	let mut set = (0..list.len()).collect();
+	let mut repeat = Repeat::new();
+
+	repeat.set_start(Some(start));
+
+	let summary = repeat.run(&mut list, &mut set);
+
+	assert!(
+		repeat
+			.repetitions()
+			.iter()
+			.all(|repetition| list.successors(repetition.start).next().is_some()),
+		"`Repeat` added a start leading nowhere"
+	);
+
+	assert!(
+		(original.len()..list.len()).all(|id| list.successors(id).next().is_some()),
+		"`Repeat` added a node leading nowhere"
+	);
+
+	let len = set.len();
+
+	repeat.set_start(repeat.new_start());
+	repeat.run(&mut list, &mut set);
+
+	assert_eq!(len, set.len(), "`Repeat` ran twice");
+
+	let result = Equivalence::new().run(&original, start, &list, summary.start.unwrap_or(start));
+
+	assert_eq!(result, Ok(()), "`Repeat` changed the program");
+});
@@ -1,25 +1,31 @@
 use arbitrary::{Arbitrary, Error, Unstructured};
-use flow_structurer::view::View;
+use flow_structurer::{
+	list::{List, Statement},
+	view::View,
+};
 
-use super::list::{List, Statement};
-
-fn list_with_elements(u: &mut Unstructured<'_>) -> Result<(List, usize), Error> {
+fn list_with_elements(u: &mut Unstructured<'_>) -> Result<(List<()>, usize), Error> {
 	let len = u.arbitrary_len::<usize>()?;
 	let mut list = List::with_capacity(len + 2);
 
-	list.add_statement(Statement::Simple);
+	list.set_duplicable(true);
+	list.add_statement(Statement::Simple(()));
 
 	for id in 1..len {
 		let predecessor = u.choose_index(id)?;
 
-		list.add_statement(Statement::Simple);
+		list.add_statement(Statement::Simple(()));
 		list.add_edge(predecessor, id);
 	}
 
 	Ok((list, len))
 }
 
-fn list_add_repeats(list: &mut List, len: usize, u: &mut Unstructured<'_>) -> Result<(), Error> {
+fn list_add_repeats(
+	list: &mut List<()>,
+	len: usize,
+	u: &mut Unstructured<'_>,
+) -> Result<(), Error> {
 	for _ in 0..u.arbitrary_len::<(usize, usize)>()? {
 		let a = u.choose_index(len)?;
 		let b = u.choose_index(len)?;
@@ -30,7 +36,11 @@ fn list_add_repeats(list: &mut List, len: usize, u: &mut Unstructured<'_>) -> Re
 	Ok(())
 }
 
-fn list_add_branches(list: &mut List, len: usize, u: &mut Unstructured<'_>) -> Result<(), Error> {
+fn list_add_branches(
+	list: &mut List<()>,
+	len: usize,
+	u: &mut Unstructured<'_>,
+) -> Result<(), Error> {
 	for _ in 0..u.arbitrary_len::<(usize, usize)>()? {
 		let a = u.choose_index(len)?;
 		let b = u.choose_index(len)?;
@@ -46,12 +56,12 @@ fn list_add_branches(list: &mut List, len: usize, u: &mut Unstructured<'_>) -> R
 }
 
 pub struct DirectedAcyclicGraph {
-	list: List,
+	list: List<()>,
 }
 
 impl DirectedAcyclicGraph {
 	#[allow(dead_code)]
-	pub fn into_inner(self) -> List {
+	pub fn into_inner(self) -> List<()> {
 		self.list
 	}
 }
@@ -74,13 +84,13 @@ impl std::fmt::Debug for DirectedAcyclicGraph {
 }
 
 pub struct DirectedGraph {
-	list: List,
+	list: List<()>,
 	start: usize,
 }
 
 impl DirectedGraph {
 	#[allow(dead_code)]
-	pub fn into_inner(self) -> (List, usize) {
+	pub fn into_inner(self) -> (List<()>, usize) {
 		(self.list, self.start)
 	}
 }
@@ -92,7 +102,7 @@ impl Arbitrary<'_> for DirectedGraph {
 		list_add_repeats(&mut list, len, u)?;
 		list_add_branches(&mut list, len, u)?;
 
-		let start = list.add_statement(Statement::Simple);
+		let start = list.add_statement(Statement::Simple(()));
 
 		list.add_edge(start, 0);
 		list.set_single_exit();
@@ -106,3 +116,76 @@ impl std::fmt::Debug for DirectedGraph {
 		self.list.fmt(f)
 	}
 }
+
+pub struct DirectedGraphWithEntries {
+	list: List<()>,
+}
+
+impl DirectedGraphWithEntries {
+	#[allow(dead_code)]
+	pub fn into_inner(self) -> List<()> {
+		self.list
+	}
+}
+
+impl Arbitrary<'_> for DirectedGraphWithEntries {
+	fn arbitrary(u: &mut Unstructured<'_>) -> Result<Self, Error> {
+		let (mut list, len) = list_with_elements(u)?;
+
+		list_add_repeats(&mut list, len, u)?;
+		list_add_branches(&mut list, len, u)?;
+
+		let entry = list.add_statement(Statement::Simple(()));
+
+		list.add_edge(entry, 0);
+
+		for _ in 0..u.arbitrary_len::<usize>()? {
+			let entry = list.add_statement(Statement::Simple(()));
+			let successor = u.choose_index(len)?;
+
+			list.add_edge(entry, successor);
+		}
+
+		list.set_single_exit();
+
+		Ok(Self { list })
+	}
+}
+
+impl std::fmt::Debug for DirectedGraphWithEntries {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		self.list.fmt(f)
+	}
+}
+
+pub struct DirectedGraphWithStart {
+	list: List<()>,
+	start: usize,
+}
+
+impl DirectedGraphWithStart {
+	#[allow(dead_code)]
+	pub fn into_inner(self) -> (List<()>, usize) {
+		(self.list, self.start)
+	}
+}
+
+impl Arbitrary<'_> for DirectedGraphWithStart {
+	fn arbitrary(u: &mut Unstructured<'_>) -> Result<Self, Error> {
+		let (mut list, len) = list_with_elements(u)?;
+
+		list_add_repeats(&mut list, len, u)?;
+		list_add_branches(&mut list, len, u)?;
+		list.set_single_exit();
+
+		let start = u.choose_index(len)?;
+
+		Ok(Self { list, start })
+	}
+}
+
+impl std::fmt::Debug for DirectedGraphWithStart {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		self.list.fmt(f)
+	}
+}
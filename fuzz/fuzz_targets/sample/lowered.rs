@@ -0,0 +1,110 @@
+use flow_structurer::{
+	equivalence::{Equivalence, Mismatch},
+	list::List,
+	view::{Classify, Kind, Successors},
+};
+
+struct Node {
+	kind: Kind,
+	origin: usize,
+	successors: Vec<usize>,
+}
+
+/// A graph lowered back out of structured code, with each node keeping the kind
+/// and origin of the one it was written from, so that it can be checked against it.
+pub struct Lowered {
+	nodes: Vec<Node>,
+}
+
+impl Lowered {
+	pub const fn new() -> Self {
+		Self { nodes: Vec::new() }
+	}
+
+	pub fn add_node(&mut self, kind: Kind, origin: usize) -> usize {
+		self.nodes.push(Node {
+			kind,
+			origin,
+			successors: Vec::new(),
+		});
+
+		self.nodes.len() - 1
+	}
+
+	pub fn add_no_operation(&mut self) -> usize {
+		self.add_node(Kind::NoOperation, usize::MAX)
+	}
+
+	pub fn add_copy<N: Classify>(&mut self, view: &N, id: usize) -> usize {
+		self.add_node(view.kind(id), view.origin(id))
+	}
+
+	pub fn add_edge(&mut self, from: usize, to: usize) {
+		self.nodes[from].successors.push(to);
+	}
+}
+
+impl Successors for Lowered {
+	fn successors(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+		self.nodes[id].successors.iter().copied()
+	}
+}
+
+impl Classify for Lowered {
+	fn kind(&self, id: usize) -> Kind {
+		self.nodes[id].kind
+	}
+
+	fn origin(&self, id: usize) -> usize {
+		self.nodes[id].origin
+	}
+}
+
+/// Structured code being lowered back into a graph of the list it was built from.
+/// Each open label keeps where continuing it goes and where breaking out of it goes.
+pub struct Lowering<'a> {
+	pub list: &'a List<()>,
+	pub lowered: Lowered,
+	pub labels: Vec<(usize, usize)>,
+	pub end: usize,
+}
+
+impl<'a> Lowering<'a> {
+	pub fn new(list: &'a List<()>) -> Self {
+		let mut lowered = Lowered::new();
+		let end = lowered.add_no_operation();
+
+		Self {
+			list,
+			lowered,
+			labels: Vec::new(),
+			end,
+		}
+	}
+
+	pub fn add_copy(&mut self, id: usize) -> usize {
+		self.lowered.add_copy(self.list, id)
+	}
+
+	pub fn add_simple(&mut self, id: usize, next: usize) -> usize {
+		let lowered = self.add_copy(id);
+
+		self.lowered.add_edge(lowered, next);
+
+		lowered
+	}
+
+	pub fn find_label(&self, depth: usize) -> (usize, usize) {
+		let index = self.labels.len().checked_sub(depth + 1);
+
+		*index
+			.and_then(|index| self.labels.get(index))
+			.expect("a jump is outside of its label")
+	}
+
+	pub fn check(&self, original: &List<()>, start: usize, entry: usize) -> Result<(), Mismatch> {
+		assert!(self.labels.is_empty(), "a label was left open");
+
+		Equivalence::new().run(original, start, &self.lowered, entry)
+	}
+}
@@ -1,2 +1,4 @@
 pub mod arbitrary;
-pub mod list;
+
+#[allow(dead_code)]
+pub mod lowered;
@@ -0,0 +1,162 @@
+#![no_main]
+
+use flow_structurer::{
+	stackifier::{Instruction, Stackifier},
+	structurer::Structurer,
+	tree::Builder,
+	view::Successors,
+};
+use libfuzzer_sys::fuzz_target;
+
+use crate::sample::{arbitrary::DirectedGraph, lowered::Lowering};
+
+mod sample;
+
+// Every node is lowered on its own, and each value it leaves on the stack is
+// followed through the control instructions to the next node that runs.
+struct Instructions<'a> {
+	instructions: &'a [Instruction],
+	labels: Vec<Vec<usize>>,
+	ends: Vec<usize>,
+	nodes: Vec<usize>,
+}
+
+impl<'a> Instructions<'a> {
+	fn new(instructions: &'a [Instruction]) -> Self {
+		let mut labels = Vec::with_capacity(instructions.len());
+		let mut ends = vec![usize::MAX; instructions.len()];
+		let mut open = Vec::new();
+
+		for (index, instruction) in instructions.iter().enumerate() {
+			labels.push(open.clone());
+
+			match instruction {
+				Instruction::Block | Instruction::Loop => open.push(index),
+				Instruction::End => {
+					let label = open.pop().expect("`End` closes nothing");
+
+					ends[label] = index;
+				}
+				_ => {}
+			}
+		}
+
+		assert!(open.is_empty(), "a block or loop is never closed");
+
+		Self {
+			instructions,
+			labels,
+			ends,
+			nodes: vec![usize::MAX; instructions.len()],
+		}
+	}
+
+	fn find_jump(&self, index: usize, depth: usize) -> usize {
+		let labels = &self.labels[index];
+		let label = labels[labels.len() - 1 - depth];
+
+		if self.instructions[label] == Instruction::Loop {
+			label + 1
+		} else {
+			self.ends[label] + 1
+		}
+	}
+
+	fn find_next(&self, mut index: usize, mut stack: Vec<usize>, end: usize) -> usize {
+		for _ in 0..=self.instructions.len() {
+			let Some(instruction) = self.instructions.get(index) else {
+				assert!(stack.is_empty(), "values were left on the stack");
+
+				return end;
+			};
+
+			index = match instruction {
+				Instruction::Node(_) => {
+					assert!(stack.is_empty(), "values were left on the stack");
+
+					return self.nodes[index];
+				}
+				Instruction::Block | Instruction::Loop | Instruction::End => index + 1,
+				&Instruction::Br(depth) => self.find_jump(index, depth),
+				&Instruction::BrIf(depth) => {
+					if stack.pop().expect("`BrIf` has no value") == 0 {
+						index + 1
+					} else {
+						self.find_jump(index, depth)
+					}
+				}
+				Instruction::BrTable { targets, default } => {
+					let value = stack.pop().expect("`BrTable` has no value");
+
+					self.find_jump(index, *targets.get(value).unwrap_or(default))
+				}
+				Instruction::Eqz => {
+					let value = stack.pop().expect("`Eqz` has no value");
+
+					stack.push(usize::from(value == 0));
+
+					index + 1
+				}
+			};
+		}
+
+		panic!("control instructions loop without running a node")
+	}
+
+	fn run(&mut self, lowering: &mut Lowering<'_>) -> usize {
+		for (index, instruction) in self.instructions.iter().enumerate() {
+			if let &Instruction::Node(id) = instruction {
+				self.nodes[index] = lowering.add_copy(id);
+			}
+		}
+
+		for (index, instruction) in self.instructions.iter().enumerate() {
+			let &Instruction::Node(id) = instruction else {
+				continue;
+			};
+
+			let count = lowering.list.successors(id).count();
+
+			if count > 1 {
+				for value in 0..count {
+					let next = self.find_next(index + 1, vec![value], lowering.end);
+
+					lowering.lowered.add_edge(self.nodes[index], next);
+				}
+			} else {
+				let next = self.find_next(index + 1, Vec::new(), lowering.end);
+
+				lowering.lowered.add_edge(self.nodes[index], next);
+			}
+		}
+
+		self.find_next(0, Vec::new(), lowering.end)
+	}
+}
+
+fuzz_target!(|built: DirectedGraph| {
+	let (mut list, start) = built.into_inner();
+	let original = list.clone();
+	let mut set = (0..list.len()).collect();
+	let mut structurer = Structurer::new();
+
+	let summary = structurer.run(&mut list, &mut set, start);
+	let moved = summary.start.unwrap_or(start);
+	let tree = Builder::new().run(
+		&list,
+		moved,
+		structurer.repeat().repetitions(),
+		structurer.branch().junctions(),
+	);
+
+	let mut stackifier = Stackifier::new();
+	let mut lowering = Lowering::new(&list);
+	let entry = Instructions::new(stackifier.run(&list, &tree)).run(&mut lowering);
+	let result = lowering.check(&original, start, entry);
+
+	assert_eq!(
+		result,
+		Ok(()),
+		"the instructions do not run as the graph does"
+	);
+});
@@ -0,0 +1,60 @@
+#![no_main]
+
+use flow_structurer::{
+	pass::{pearce_finder::PearceFinder, strongly_connected_finder::StronglyConnectedFinder},
+	set::Set,
+	view::Successors,
+};
+use libfuzzer_sys::fuzz_target;
+
+use crate::sample::arbitrary::DirectedGraph;
+
+mod sample;
+
+fn add_component(components: &mut Vec<Vec<usize>>, list: &[usize]) {
+	if list.is_empty() {
+		return;
+	}
+
+	let mut component = list.to_vec();
+
+	component.sort_unstable();
+	components.push(component);
+}
+
+fuzz_target!(|built: DirectedGraph| {
+	let (list, _) = built.into_inner();
+	let set: Set = (0..list.len()).collect();
+	let mut expected = Vec::new();
+	let mut found = Vec::new();
+
+	StronglyConnectedFinder::new().run(&list, set.as_slice(), |component| {
+		add_component(&mut expected, component);
+	});
+
+	PearceFinder::new().run(&list, set.as_slice(), |component| {
+		add_component(&mut found, component);
+	});
+
+	// Components must be found after all of those they lead to.
+	let mut positions = vec![0; list.len()];
+
+	for (position, component) in found.iter().enumerate() {
+		for &id in component {
+			positions[id] = position;
+		}
+	}
+
+	for id in 0..list.len() {
+		assert!(
+			list.successors(id)
+				.all(|successor| positions[successor] <= positions[id]),
+			"`PearceFinder` found components out of order"
+		);
+	}
+
+	expected.sort_unstable();
+	found.sort_unstable();
+
+	assert_eq!(expected, found, "`PearceFinder` found different components");
+});
@@ -0,0 +1,39 @@
+#![no_main]
+
+use flow_structurer::{
+	branch::Branch, repeat::Repeat, structurer::Structurer, verify::Verifier, view::Successors,
+};
+use libfuzzer_sys::fuzz_target;
+
+use crate::sample::arbitrary::DirectedGraph;
+
+mod sample;
+
+fuzz_target!(|built: DirectedGraph| {
+	let (mut list, start) = built.into_inner();
+	let mut set = (0..list.len()).collect();
+
+	let summary = Structurer::new().run(&mut list, &mut set, start);
+
+	let is_endless = set
+		.ascending()
+		.all(|id| list.successors(id).next().is_some());
+
+	assert!(
+		summary.exit.is_some() || is_endless,
+		"`Structurer` left many exits"
+	);
+
+	let result = Verifier::new().run(&list, set.as_slice(), start);
+
+	assert_eq!(result, Ok(()), "`Structurer` left regions unstructured");
+
+	let len = set.len();
+	let summary = Repeat::new().run(&mut list, &mut set);
+
+	assert!(!summary.changed && len == set.len(), "`Repeat` ran twice");
+
+	let summary = Branch::new().run(&mut list, &mut set, start);
+
+	assert!(!summary.changed && len == set.len(), "`Branch` ran twice");
+});
@@ -0,0 +1,28 @@
+#![no_main]
+
+use flow_structurer::{equivalence::Equivalence, structurer::Structurer, verify::Verifier};
+use libfuzzer_sys::fuzz_target;
+
+use crate::sample::arbitrary::DirectedGraph;
+
+mod sample;
+
+const BUDGET: usize = 16;
+
+fuzz_target!(|built: DirectedGraph| {
+	let (mut list, start) = built.into_inner();
+	let original = list.clone();
+	let mut set = (0..list.len()).collect();
+	let mut structurer = Structurer::new();
+
+	structurer.set_budget(BUDGET);
+	structurer.run(&mut list, &mut set, start);
+
+	let result = Verifier::new().run(&list, set.as_slice(), start);
+
+	assert_eq!(result, Ok(()), "`Structurer` left the graph unstructured");
+
+	let result = Equivalence::new().run(&original, start, &list, start);
+
+	assert_eq!(result, Ok(()), "duplicating nodes changed the program");
+});
@@ -0,0 +1,25 @@
+#![no_main]
+
+use flow_structurer::{
+	pass::entry_unifier::EntryUnifier, structurer::Structurer, verify::Verifier,
+};
+use libfuzzer_sys::fuzz_target;
+
+use crate::sample::arbitrary::DirectedGraphWithEntries;
+
+mod sample;
+
+fuzz_target!(|built: DirectedGraphWithEntries| {
+	let mut list = built.into_inner();
+	let mut set = (0..list.len()).collect();
+
+	let Some(start) = EntryUnifier::new().run(&mut list, &mut set) else {
+		return;
+	};
+
+	Structurer::new().run(&mut list, &mut set, start);
+
+	let result = Verifier::new().run(&list, set.as_slice(), start);
+
+	assert_eq!(result, Ok(()), "`Structurer` left regions unstructured");
+});
@@ -0,0 +1,20 @@
+#![no_main]
+
+use flow_structurer::{equivalence::Equivalence, structurer::Structurer};
+use libfuzzer_sys::fuzz_target;
+
+use crate::sample::arbitrary::DirectedGraph;
+
+mod sample;
+
+fuzz_target!(|built: DirectedGraph| {
+	let (mut list, start) = built.into_inner();
+	let original = list.clone();
+	let mut set = (0..list.len()).collect();
+
+	Structurer::new().run(&mut list, &mut set, start);
+
+	let result = Equivalence::new().run(&original, start, &list, start);
+
+	assert_eq!(result, Ok(()), "`Structurer` changed the program");
+});
@@ -0,0 +1,15 @@
+#![no_main]
+
+use flow_structurer::structurer::Structurer;
+use libfuzzer_sys::fuzz_target;
+
+use crate::sample::arbitrary::DirectedGraph;
+
+mod sample;
+
+fuzz_target!(|built: DirectedGraph| {
+	let (mut list, start) = built.into_inner();
+	let mut set = (0..list.len()).collect();
+
+	Structurer::new().run(&mut list, &mut set, start);
+});
@@ -0,0 +1,89 @@
+#![no_main]
+
+use flow_structurer::{
+	structurer::Structurer,
+	tree::{Builder, Node},
+	view::Successors,
+};
+use libfuzzer_sys::fuzz_target;
+
+use crate::sample::{arbitrary::DirectedGraph, lowered::Lowering};
+
+mod sample;
+
+// Loops are entered through a new node, which their latches go back to, and
+// every other structure falls through to what follows it.
+impl Lowering<'_> {
+	fn add_latch(&mut self, id: usize, next: usize, start: usize) -> usize {
+		let lowered = self.add_copy(id);
+		let (entry, _) = self.find_label(0);
+
+		for successor in self.list.successors(id) {
+			let target = if successor == start { entry } else { next };
+
+			self.lowered.add_edge(lowered, target);
+		}
+
+		lowered
+	}
+
+	fn add_node(&mut self, node: &Node, next: usize, repetition: Option<(usize, usize)>) -> usize {
+		match node {
+			&Node::Simple(id) => match repetition {
+				Some((start, latch)) if latch == id => self.add_latch(id, next, start),
+				_ => self.add_simple(id, next),
+			},
+			Node::Sequence(list) => list
+				.iter()
+				.rev()
+				.fold(next, |next, node| self.add_node(node, next, repetition)),
+			Node::Branch { head, arms, .. } => {
+				let lowered = self.add_copy(*head);
+
+				for arm in arms {
+					let arm = self.add_node(arm, next, repetition);
+
+					self.lowered.add_edge(lowered, arm);
+				}
+
+				lowered
+			}
+			Node::Loop {
+				start, latch, body, ..
+			} => {
+				let entry = self.lowered.add_no_operation();
+
+				self.labels.push((entry, next));
+
+				let body = self.add_node(body, next, Some((*start, *latch)));
+
+				self.labels.pop();
+				self.lowered.add_edge(entry, body);
+
+				entry
+			}
+		}
+	}
+}
+
+fuzz_target!(|built: DirectedGraph| {
+	let (mut list, start) = built.into_inner();
+	let original = list.clone();
+	let mut set = (0..list.len()).collect();
+	let mut structurer = Structurer::new();
+
+	let summary = structurer.run(&mut list, &mut set, start);
+	let moved = summary.start.unwrap_or(start);
+	let tree = Builder::new().run(
+		&list,
+		moved,
+		structurer.repeat().repetitions(),
+		structurer.branch().junctions(),
+	);
+
+	let mut lowering = Lowering::new(&list);
+	let entry = lowering.add_node(&tree, lowering.end, None);
+	let result = lowering.check(&original, start, entry);
+
+	assert_eq!(result, Ok(()), "the tree does not run as the graph does");
+});
@@ -1,17 +1,44 @@
 use crate::{
+	error::StructureError,
+	pass::{dominator_finder::DominatorFinder, inverted::Inverted},
 	set::{Set, Slice},
-	view::{Successors, View},
+	summary::{Counter, Summary},
+	verify::Verifier,
+	view::{Predecessors, Successors, View},
 };
 
-use super::single::Single;
+use super::single::{Dominance, Junction, Single};
+
+// A region left to structure, either as its set of nodes or as the subtree of the
+// dominator tree at its head, along with the node leaving it and the post-dominator
+// it leads to.
+enum Region {
+	Nodes(Set),
+	Subtree {
+		exit: Option<usize>,
+		post: Option<usize>,
+	},
+}
 
 /// This structure implements a bulk recursive algorithm to restructure a set of nodes.
-/// More details are provided in [`Single`].
+/// More details are provided in [`Single`]. Branches that meet again at the immediate
+/// post-dominator of their head are structured from the dominator tree when possible.
 pub struct Bulk {
-	found: Vec<(Set, usize)>,
+	found: Vec<(Region, usize)>,
 	pool: Vec<Set>,
+	junctions: Vec<Junction>,
+	leaving: Vec<usize>,
+	merging: Vec<usize>,
+	exits: Vec<usize>,
+	budget: usize,
+	remaining: usize,
+	has_post_dominators: bool,
+	has_dominators: bool,
 
 	single: Single,
+	verifier: Verifier,
+	dominator_finder: DominatorFinder,
+	post_dominator_finder: DominatorFinder,
 }
 
 impl Bulk {
@@ -21,11 +48,39 @@ impl Bulk {
 		Self {
 			found: Vec::new(),
 			pool: Vec::new(),
+			junctions: Vec::new(),
+			leaving: Vec::new(),
+			merging: Vec::new(),
+			exits: Vec::new(),
+			budget: 0,
+			remaining: 0,
+			has_post_dominators: false,
+			has_dominators: false,
 
 			single: Single::new(),
+			verifier: Verifier::new(),
+			dominator_finder: DominatorFinder::new(),
+			post_dominator_finder: DominatorFinder::new(),
 		}
 	}
 
+	/// Returns the branches structured by the last run, in the order they were structured.
+	#[must_use]
+	pub fn junctions(&self) -> &[Junction] {
+		&self.junctions
+	}
+
+	/// Sets the total cost of the nodes that may be duplicated in a run. Branches with
+	/// many continuations are given a single one by duplicating nodes while the budget
+	/// allows for it, and by adding a selection otherwise. The default is `0`.
+	pub fn set_budget(&mut self, budget: usize) {
+		self.budget = budget;
+	}
+
+	pub(crate) fn pool_mut(&mut self) -> &mut Vec<Set> {
+		&mut self.pool
+	}
+
 	fn find_next_branch<N: Successors>(view: &N, start: &mut usize, set: &mut Set) -> bool {
 		loop {
 			// We ignore loops, either self loops or a successor that was already visited.
@@ -33,49 +88,412 @@ impl Bulk {
 
 			let mut successors = view.successors(*start).filter(|&id| set.contains(id));
 
-			if let (Some(successor), None) = (successors.next(), successors.next()) {
-				*start = successor;
-			} else {
-				break;
+			match (successors.next(), successors.next()) {
+				(Some(successor), None) => *start = successor,
+				(Some(_), Some(_)) => return true,
+				(None, _) => return !set.is_empty(),
 			}
 		}
-
-		!set.is_empty()
 	}
 
 	fn queue_if_branch<N: View>(&mut self, view: &N, mut start: usize, mut set: Set) {
 		if Self::find_next_branch(view, &mut start, &mut set) {
-			self.found.push((set, start));
+			self.found.push((Region::Nodes(set), start));
 		} else {
 			self.pool.push(set);
 		}
 	}
 
-	fn run_single<N: View>(&mut self, view: &mut N, head: usize, set: Slice) {
-		let last = self.single.run(view, head, set, &mut self.pool);
+	// As in `find_next_branch`, but the region is the subtree at the root along with its
+	// exit, and the loops ignored are the successors dominating the node.
+	fn find_next_dominated_branch<N: Successors>(
+		&self,
+		view: &N,
+		root: usize,
+		start: &mut usize,
+		exit: Option<usize>,
+	) -> bool {
+		let dominators = &self.dominator_finder;
+
+		loop {
+			let mut successors = view.successors(*start).filter(|&id| {
+				(Some(id) == exit || dominators.dominates(root, id))
+					&& !dominators.dominates(id, *start)
+			});
+
+			match (successors.next(), successors.next()) {
+				(Some(successor), None) => *start = successor,
+				(Some(_), Some(_)) => return true,
+				(None, _) => return false,
+			}
+		}
+	}
+
+	fn queue_if_dominated_branch<N: View>(
+		&mut self,
+		view: &N,
+		root: usize,
+		exit: Option<usize>,
+		post: Option<usize>,
+	) {
+		let mut start = root;
+
+		if self.find_next_dominated_branch(view, root, &mut start, exit) {
+			self.found.push((Region::Subtree { exit, post }, start));
+		}
+	}
+
+	fn run_single<N: View>(&mut self, view: &mut N, head: usize, set: &mut Set) {
+		let post_dominators = self
+			.has_post_dominators
+			.then_some(&self.post_dominator_finder);
+
+		let last = self.single.run(
+			view,
+			head,
+			set,
+			post_dominators,
+			&mut self.pool,
+			self.remaining,
+		);
+
+		self.remaining -= self.single.cost();
+
+		self.junctions.push(Junction {
+			head,
+			continuation: last,
+		});
+
 		let tail = std::mem::replace(self.single.tail_mut(), self.pool.pop().unwrap_or_default());
 
-		self.queue_if_branch(view, last, tail);
+		if let Some(last) = last {
+			self.queue_if_branch(view, last, tail);
+		} else {
+			self.pool.push(tail);
+		}
 
 		while let Some((set, start)) = self.single.branches_mut().pop() {
+			self.queue_arm(view, start, set);
+		}
+	}
+
+	// An arm that is still the subtree of its start, apart from a new node leaving it,
+	// is structured from the dominator tree again. It stands for the first post-dominator
+	// of its start outside of it.
+	fn find_dominated_arm<N: Successors>(
+		&self,
+		view: &N,
+		start: usize,
+		set: &Set,
+	) -> Option<(Option<usize>, Option<usize>)> {
+		let dominators = &self.dominator_finder;
+		let mut exit = None;
+		let mut len = 0;
+
+		if !self.has_dominators || dominators.tree_number(start).is_none() {
+			return None;
+		}
+
+		for id in set.ascending() {
+			if dominators.tree_number(id).is_none() {
+				if exit.replace(id).is_some() {
+					return None;
+				}
+			} else if dominators.dominates(start, id) {
+				len += 1;
+			} else {
+				return None;
+			}
+		}
+
+		if len != dominators.subtree(start).len()
+			|| exit.is_some_and(|exit| view.successors(exit).any(|id| set.contains(id)))
+		{
+			return None;
+		}
+
+		let post = exit.and_then(|_| {
+			let mut id = start;
+
+			loop {
+				id = self.post_dominator_finder.immediate(id)?;
+
+				if !dominators.dominates(start, id) {
+					return Some(id);
+				}
+			}
+		});
+
+		Some((exit, post))
+	}
+
+	fn queue_arm<N: View>(&mut self, view: &N, start: usize, set: Set) {
+		if let Some((exit, post)) = self.find_dominated_arm(view, start, &set) {
+			self.pool.push(set);
+			self.queue_if_dominated_branch(view, start, exit, post);
+		} else {
 			self.queue_if_branch(view, start, set);
 		}
 	}
 
-	/// Restructures the nodes in the given set.
-	pub fn run<N: View>(&mut self, view: &mut N, set: &mut Set, start: usize) {
-		let mut original = self.pool.pop().unwrap_or_default();
+	// The arms of a branch meeting at a node of the dominator tree lead to it, while
+	// those meeting at the exit of their region stand for where that leads.
+	fn run_dominated<N: View>(
+		&mut self,
+		view: &mut N,
+		head: usize,
+		exit: Option<usize>,
+		post: Option<usize>,
+	) -> bool {
+		let dominance = Dominance {
+			dominators: &self.dominator_finder,
+			post_dominators: &self.post_dominator_finder,
+			leaving: &self.leaving,
+		};
+
+		let Some(continuation) = self
+			.single
+			.run_dominated(view, head, exit, post, &dominance)
+		else {
+			return false;
+		};
+
+		self.junctions.push(Junction {
+			head,
+			continuation: Some(continuation),
+		});
+
+		let arm_post = if self.dominator_finder.tree_number(continuation).is_some() {
+			Some(continuation)
+		} else {
+			post
+		};
+
+		self.queue_if_dominated_branch(view, continuation, exit, post);
+
+		while let Some((start, exit)) = self.single.arms_mut().pop() {
+			self.queue_if_dominated_branch(view, start, Some(exit), arm_post);
+		}
+
+		true
+	}
+
+	// A branch that meets again where its head dominates, but not only there, is given the
+	// nodes up to that continuation, with those after it kept to be structured as its subtree,
+	// unless the continuation is repeated. Otherwise, it is given all the nodes it dominates.
+	fn find_dominated_continuation<N: Predecessors>(&self, view: &N, head: usize) -> Option<usize> {
+		let dominators = &self.dominator_finder;
+		let continuation = self.post_dominator_finder.immediate(head)?;
+
+		(dominators.dominates(head, continuation)
+			&& !view
+				.predecessors(continuation)
+				.any(|id| dominators.dominates(continuation, id)))
+		.then_some(continuation)
+	}
+
+	// Branches that can not be structured from the dominator tree are given the nodes
+	// they would have had as a set, which are those dominated by the head and the exit.
+	fn run_subtree<N: View>(
+		&mut self,
+		view: &mut N,
+		head: usize,
+		exit: Option<usize>,
+		post: Option<usize>,
+	) {
+		if self.run_dominated(view, head, exit, post) {
+			return;
+		}
+
+		let mut branch = self.pool.pop().unwrap_or_default();
+
+		branch.clear();
+
+		if let Some(continuation) = self.find_dominated_continuation(view, head) {
+			let nodes = self.dominator_finder.subtree(head);
+			let after = self.dominator_finder.subtree(continuation);
+			let first = self.dominator_finder.tree_number(head).unwrap();
+			let last = self.dominator_finder.tree_number(continuation).unwrap() - first;
+
+			branch.extend(nodes[1..=last].iter().copied());
+			branch.extend(nodes[last + after.len()..].iter().copied());
+
+			self.queue_if_dominated_branch(view, continuation, exit, post);
+		} else {
+			branch.extend(self.dominator_finder.subtree(head)[1..].iter().copied());
+
+			if let Some(exit) = exit.filter(|&id| self.dominator_finder.tree_number(id).is_none()) {
+				branch.grow_insert(exit);
+			}
+		}
+
+		self.run_single(view, head, &mut branch);
+		self.pool.push(branch);
+	}
+
+	// An edge leaves the subtrees of the nodes from its source up to, but excluding, the
+	// nearest common dominator of its ends, so it is counted at the source and discounted
+	// there, with every subtree then summing its nodes.
+	fn find_leaving<N: Successors>(&mut self, view: &N) {
+		let dominators = &self.dominator_finder;
+		let Some(start) = dominators.reverse_post_order().next() else {
+			return;
+		};
+
+		let len = dominators
+			.subtree(start)
+			.iter()
+			.max()
+			.map_or(0, |id| id + 1);
 
-		original.clone_from(set);
+		if self.leaving.len() < len {
+			self.leaving.resize(len, 0);
+			self.merging.resize(len, 0);
+		}
 
-		self.queue_if_branch(view, start, original);
+		for &id in dominators.subtree(start) {
+			self.leaving[id] = 0;
+			self.merging[id] = 0;
+		}
 
-		while let Some((branch, start)) = self.found.pop() {
-			self.run_single(view, start, branch.as_slice());
+		for &id in dominators.subtree(start) {
+			for successor in view.successors(id) {
+				self.leaving[id] += 1;
+
+				if dominators.dominates(successor, id) {
+					self.merging[successor] += 1;
+				} else if let Some(dominator) = dominators.immediate(successor) {
+					self.merging[dominator] += 1;
+				}
+			}
+		}
+
+		for &id in dominators.subtree(start)[1..].iter().rev() {
+			let dominator = dominators.immediate(id).unwrap();
+
+			self.leaving[dominator] += self.leaving[id];
+			self.merging[dominator] += self.merging[id];
+			self.leaving[id] -= self.merging[id];
+		}
+
+		self.leaving[start] -= self.merging[start];
+	}
+
+	// The dominator tree is only used when every node is entered from within the set and
+	// reaches its exit, as the post-dominators say nothing about where repetitions that
+	// never end are reached from.
+	fn find_dominators<N: View>(&mut self, view: &N, set: Slice, start: usize) {
+		self.has_dominators = false;
+
+		if !self.has_post_dominators {
+			return;
+		}
+
+		self.dominator_finder.run(view, set, start);
+
+		let dominators = &self.dominator_finder;
+		let post_dominators = &self.post_dominator_finder;
+
+		self.has_dominators = set.into_iter().all(|id| {
+			dominators.tree_number(id).is_some()
+				&& post_dominators.tree_number(id).is_some()
+				&& (id == start || view.predecessors(id).all(|id| set.contains(id)))
+		});
+
+		if self.has_dominators {
+			self.find_leaving(view);
+		}
+	}
+
+	fn find_post_dominators<N: View>(&mut self, view: &N, set: Slice) {
+		self.find_exits(view, set);
+
+		self.has_post_dominators = self.exits.len() == 1;
+
+		if let &[exit] = self.exits.as_slice() {
+			self.post_dominator_finder.run(&Inverted(view), set, exit);
+		}
+	}
+
+	pub(crate) fn run_counted<N: View>(&mut self, view: &mut N, set: &mut Set, start: usize) {
+		self.junctions.clear();
+		self.remaining = self.budget;
+		self.find_post_dominators(view, set.as_slice());
+		self.find_dominators(view, set.as_slice(), start);
+
+		if self.has_dominators {
+			self.queue_if_dominated_branch(view, start, None, None);
+		} else {
+			let mut original = self.pool.pop().unwrap_or_default();
+
+			original.clone_from(set);
+
+			self.queue_if_branch(view, start, original);
+		}
+
+		while let Some((region, start)) = self.found.pop() {
+			match region {
+				Region::Nodes(mut branch) => {
+					self.run_single(view, start, &mut branch);
+					self.pool.push(branch);
+				}
+				Region::Subtree { exit, post } => self.run_subtree(view, start, exit, post),
+			}
 
 			set.extend(self.single.additional().iter().copied());
+		}
+	}
+
+	fn find_exits<N: Successors>(&mut self, view: &N, set: Slice) {
+		self.exits.clear();
+		self.exits.extend(
+			set.into_iter()
+				.filter(|&id| !view.successors(id).any(|id| set.contains(id))),
+		);
+	}
+
+	/// Validates that the start is in the given set, that its repetitions are normalized
+	/// so that it is otherwise acyclic, and that it has at most one exit, then restructures
+	/// the nodes as [`Bulk::run`] does. A set without exits only has repetitions that
+	/// never end.
+	///
+	/// # Errors
+	///
+	/// Returns the first precondition broken, in which case nothing is changed.
+	pub fn try_run<N: View>(
+		&mut self,
+		view: &mut N,
+		set: &mut Set,
+		start: usize,
+	) -> Result<Summary, StructureError> {
+		if !set.contains(start) {
+			return Err(StructureError::Outside { start });
+		}
+
+		self.verifier.run_repetitions(view, set.as_slice(), start)?;
+
+		self.find_exits(view, set.as_slice());
+
+		if self.exits.len() > 1 {
+			return Err(StructureError::Exits {
+				exits: self.exits.clone(),
+			});
+		}
+
+		Ok(self.run(view, set, start))
+	}
+
+	/// Restructures the nodes in the given set and returns a summary of the changes.
+	pub fn run<N: View>(&mut self, view: &mut N, set: &mut Set, start: usize) -> Summary {
+		let mut counter = Counter::new(view);
+
+		self.run_counted(&mut counter, set, start);
+
+		let exit = Summary::find_exit(counter.view(), set.as_slice());
 
-			self.pool.push(branch);
+		Summary {
+			exit,
+			..counter.into_summary()
 		}
 	}
 }
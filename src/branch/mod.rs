@@ -2,3 +2,4 @@ mod bulk;
 mod single;
 
 pub use bulk::Bulk as Branch;
+pub use single::Junction;
@@ -1,21 +1,69 @@
 use crate::{
-	pass::depth_first_searcher::DepthFirstSearcher,
+	pass::{
+		depth_first_searcher::DepthFirstSearcher, dominator_finder::DominatorFinder,
+		duplicator::Duplicator, inverted::Inverted,
+	},
 	set::{Set, Slice},
 	view::{Flag, Predecessors, Successors, View},
 };
 
+/// A structured branch, with the head node which selects an arm and the
+/// continuation node where all arms meet again. There is no continuation
+/// when the arms never meet, such as when they end in repetitions without exits.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Junction {
+	pub head: usize,
+	pub continuation: Option<usize>,
+}
+
+/// The dominator and post-dominator trees of a region, found before it was restructured,
+/// along with the number of edges leaving the subtree of every node in the dominator tree.
+pub struct Dominance<'a> {
+	pub dominators: &'a DominatorFinder,
+	pub post_dominators: &'a DominatorFinder,
+	pub leaving: &'a [usize],
+}
+
 /// This structure implements a single pass of this algorithm. It assumes that the set
 /// provided is a branch construct and that the start node is the head of that branch.
 /// Additionally, all strongly connected components are assumed to have been normalized.
+///
+/// The continuation is the immediate post-dominator of the head when the set has a single
+/// node leaving it, and the arms are only entered from the head and only leave to the
+/// continuation. Otherwise, nodes with predecessors from many arms are moved to the tail,
+/// and the continuations are the nodes of the tail entered from outside of it, if any.
+/// When allowed a budget, the nodes between the many continuations and where they meet
+/// are duplicated into each arm, instead of adding a selection on [`Flag::A`].
 pub struct Single {
 	branches: Vec<(Set, usize)>,
+	arms: Vec<(usize, usize)>,
+	sorted: Vec<(usize, usize)>,
+	merged: Vec<(usize, usize)>,
 	tail: Set,
 	continuations: Vec<usize>,
+	exits: Vec<(usize, usize)>,
+	searched: Vec<usize>,
+
+	order: Vec<usize>,
+	positions: Vec<usize>,
+	reached: Vec<usize>,
+	merge: Set,
+	region: Set,
+	claimed: Set,
+	reaching: Set,
+	edges: Vec<(usize, usize, usize)>,
+	shared: Vec<usize>,
+	owned: Vec<usize>,
+	bounds: Vec<(usize, usize)>,
 
 	temporary: Vec<usize>,
 	additional: Vec<usize>,
+	duplicates: Vec<usize>,
+	cost: usize,
 
 	depth_first_searcher: DepthFirstSearcher,
+	dominator_finder: DominatorFinder,
+	duplicator: Duplicator,
 }
 
 impl Single {
@@ -24,13 +72,34 @@ impl Single {
 	pub const fn new() -> Self {
 		Self {
 			branches: Vec::new(),
+			arms: Vec::new(),
+			sorted: Vec::new(),
+			merged: Vec::new(),
 			tail: Set::new(),
 			continuations: Vec::new(),
+			exits: Vec::new(),
+			searched: Vec::new(),
+
+			order: Vec::new(),
+			positions: Vec::new(),
+			reached: Vec::new(),
+			merge: Set::new(),
+			region: Set::new(),
+			claimed: Set::new(),
+			reaching: Set::new(),
+			edges: Vec::new(),
+			shared: Vec::new(),
+			owned: Vec::new(),
+			bounds: Vec::new(),
 
 			temporary: Vec::new(),
 			additional: Vec::new(),
+			duplicates: Vec::new(),
+			cost: 0,
 
 			depth_first_searcher: DepthFirstSearcher::new(),
+			dominator_finder: DominatorFinder::new(),
+			duplicator: Duplicator::new(),
 		}
 	}
 
@@ -40,6 +109,13 @@ impl Single {
 		&mut self.branches
 	}
 
+	/// Returns the arms of the branch restructured by [`Single::run_dominated`], each
+	/// with its start and the only node leaving it.
+	#[must_use]
+	pub fn arms_mut(&mut self) -> &mut Vec<(usize, usize)> {
+		&mut self.arms
+	}
+
 	/// Returns the tail set of the restructured branch.
 	#[must_use]
 	pub fn tail_mut(&mut self) -> &mut Set {
@@ -52,6 +128,13 @@ impl Single {
 		&self.additional
 	}
 
+	/// Returns the cost of the nodes duplicated during the restructuring, as charged
+	/// against the budget.
+	#[must_use]
+	pub const fn cost(&self) -> usize {
+		self.cost
+	}
+
 	fn retain_branches_if<P: Fn(&Set) -> bool>(&mut self, pool: &mut Vec<Set>, predicate: P) {
 		// When `extract_if` is stable it should replace this.
 		self.branches.retain_mut(|(set, _)| {
@@ -131,8 +214,66 @@ impl Single {
 		}
 	}
 
-	// We must ensure either all assignments are in the tail or none are.
+	// Arms that repeat forever can share a tail that the others never reach, so whatever
+	// leaves the set is moved to it, leaving each arm with a single way out. Nodes that
+	// only leave the set are moved whole, while other edges leaving it get a new node.
+	fn set_exits_in_tail<N: View>(
+		&mut self,
+		view: &mut N,
+		head: usize,
+		set: &mut Set,
+		pool: &mut Vec<Set>,
+	) {
+		if self.tail.is_empty() {
+			return;
+		}
+
+		for (branch, _) in &mut self.branches {
+			self.temporary.clear();
+			self.temporary.extend(
+				branch
+					.ascending()
+					.filter(|&id| view.successors(id).all(|id| !set.contains(id))),
+			);
+
+			for &id in &self.temporary {
+				branch.remove(id);
+
+				self.tail.grow_insert(id);
+			}
+		}
+
+		self.retain_branches_if(pool, |set| !set.is_empty());
+
+		self.exits.clear();
+
+		for id in
+			std::iter::once(head).chain(self.branches.iter().flat_map(|(set, _)| set.ascending()))
+		{
+			self.exits.extend(
+				view.successors(id)
+					.filter(|&id| !set.contains(id))
+					.map(|exit| (id, exit)),
+			);
+		}
+
+		for &(from, to) in &self.exits {
+			let dummy = view.add_no_operation();
+
+			view.replace_edge(from, to, dummy);
+			view.add_edge(dummy, to);
+
+			set.grow_insert(dummy);
+
+			self.tail.grow_insert(dummy);
+			self.additional.push(dummy);
+		}
+	}
+
+	// We must ensure either all assignments are in the tail or none are. Those left in
+	// the arms would also be overwritten when a selection is added after them.
 	fn has_orphan_assignments<N: View>(&self, view: &N) -> bool {
+		let is_selected = self.continuations.len() > 1;
 		let mut has_in_tail = false;
 		let mut has_in_branch = false;
 
@@ -142,7 +283,7 @@ impl Single {
 					has_in_tail |= self.tail.contains(id);
 					has_in_branch |= !self.tail.contains(id);
 
-					if has_in_tail && has_in_branch {
+					if has_in_branch && (has_in_tail || is_selected) {
 						return true;
 					}
 				}
@@ -204,6 +345,442 @@ impl Single {
 		self.retain_branches_if(pool, |set| !set.is_empty());
 	}
 
+	fn find_merge<N: Successors>(&mut self, view: &N) -> Option<usize> {
+		let reached = &mut self.reached;
+
+		reached.clear();
+
+		for &continuation in &self.continuations {
+			self.depth_first_searcher.nodes_mut().clone_from(&self.tail);
+			self.depth_first_searcher
+				.run(view, continuation, |id, post| {
+					if !post {
+						return;
+					}
+
+					if reached.len() <= id {
+						reached.resize(id + 1, 0);
+					}
+
+					reached[id] += 1;
+				});
+		}
+
+		self.order.clear();
+		self.depth_first_searcher.nodes_mut().clone_from(&self.tail);
+
+		for &continuation in &self.continuations {
+			self.depth_first_searcher
+				.run(view, continuation, |id, post| {
+					if post {
+						self.order.push(id);
+					}
+				});
+		}
+
+		// The merge is the first node, in reverse post-order, reached by every continuation.
+		self.order
+			.iter()
+			.rev()
+			.copied()
+			.find(|&id| self.reached[id] == self.continuations.len())
+	}
+
+	fn is_in_region(&self, id: usize) -> bool {
+		self.positions
+			.get(id)
+			.is_some_and(|&position| position != usize::MAX)
+			&& !self.merge.contains(id)
+	}
+
+	// The region between the continuations and the merge must not repeat, must only
+	// leave through the merge, and nothing after the merge may be entered from it.
+	// Nodes without successors, as when other continuations repeat forever, never do.
+	fn find_region<N: View>(&mut self, view: &N, merge: usize) -> bool {
+		self.merge.clear();
+		self.depth_first_searcher.nodes_mut().clone_from(&self.tail);
+		self.depth_first_searcher.run(view, merge, |id, post| {
+			if post {
+				self.merge.grow_insert(id);
+			}
+		});
+
+		self.positions.clear();
+
+		for (position, &id) in self.order.iter().enumerate() {
+			if self.positions.len() <= id {
+				self.positions.resize(id + 1, usize::MAX);
+			}
+
+			self.positions[id] = position;
+		}
+
+		self.region.clear();
+		self.region.extend(
+			self.order
+				.iter()
+				.copied()
+				.filter(|&id| !self.merge.contains(id)),
+		);
+
+		let is_forward = |from: usize, to: usize| {
+			to == merge || (self.is_in_region(to) && self.positions[to] < self.positions[from])
+		};
+
+		let is_closed =
+			|id: usize| id == merge || view.predecessors(id).all(|id| self.merge.contains(id));
+
+		self.region.ascending().all(|id| {
+			view.successors(id).next().is_some()
+				&& view
+					.successors(id)
+					.all(|successor| is_forward(id, successor))
+		}) && self.merge.ascending().all(is_closed)
+	}
+
+	// Every arm entering the region is a group, as is every edge from the head into it.
+	fn find_region_edges<N: Successors + Predecessors>(&mut self, view: &N, head: usize) -> bool {
+		self.edges.clear();
+
+		for id in self.region.ascending() {
+			for predecessor in view.predecessors(id) {
+				if self.region.contains(predecessor) || predecessor == head {
+					continue;
+				}
+
+				let Some(group) = self
+					.branches
+					.iter()
+					.position(|(set, _)| set.contains(predecessor))
+				else {
+					return false;
+				};
+
+				self.edges.push((group, predecessor, id));
+			}
+		}
+
+		let count = self.branches.len();
+
+		for (index, successor) in view.successors(head).enumerate() {
+			if self.region.contains(successor) {
+				self.edges.push((count + index, head, successor));
+			}
+		}
+
+		self.edges.sort_unstable();
+
+		true
+	}
+
+	// Each group copies the nodes it reaches which an earlier group also reaches,
+	// and owns the nodes that it reaches first.
+	fn find_shared<N: View>(&mut self, view: &N) -> usize {
+		let mut cost = 0;
+
+		self.claimed.clear();
+		self.shared.clear();
+		self.owned.clear();
+		self.bounds.clear();
+
+		for group in self.edges.chunk_by(|a, b| a.0 == b.0) {
+			self.depth_first_searcher
+				.nodes_mut()
+				.clone_from(&self.region);
+
+			for &(_, _, id) in group {
+				self.depth_first_searcher.run(view, id, |id, post| {
+					if post {
+						return;
+					}
+
+					if self.claimed.contains(id) {
+						cost += view.cost(id);
+
+						self.shared.push(id);
+					} else {
+						self.owned.push(id);
+					}
+				});
+			}
+
+			self.claimed.extend(
+				self.owned[self.bounds.last().map_or(0, |b| b.1)..]
+					.iter()
+					.copied(),
+			);
+			self.bounds.push((self.shared.len(), self.owned.len()));
+		}
+
+		cost
+	}
+
+	fn find_copy_of(&self, start: usize, end: usize, id: usize) -> Option<usize> {
+		self.shared[start..end]
+			.iter()
+			.position(|&shared| shared == id)
+			.map(|index| self.duplicates[start + index])
+	}
+
+	fn set_copy_edge<N: View>(&self, view: &mut N, range: (usize, usize), from: usize, to: usize) {
+		if let Some(copy) = self.find_copy_of(range.0, range.1, to) {
+			view.replace_edge(from, to, copy);
+		}
+	}
+
+	// All copies are made before any edge is redirected, as each group copies
+	// the edges of the nodes as they were.
+	fn set_shared_copies<N: View>(&mut self, view: &mut N) {
+		let mut start = 0;
+
+		for &(end, _) in &self.bounds {
+			self.duplicator.run(view, &self.shared[start..end]);
+			self.duplicates.extend(self.duplicator.copies());
+
+			start = end;
+		}
+
+		let mut start = (0, 0);
+
+		for (group, &end) in self.edges.chunk_by(|a, b| a.0 == b.0).zip(&self.bounds) {
+			let range = (start.0, end.0);
+
+			for &(_, predecessor, id) in group {
+				self.set_copy_edge(view, range, predecessor, id);
+			}
+
+			for &owned in &self.owned[start.1..end.1] {
+				self.temporary.clear();
+				self.temporary.extend(view.successors(owned));
+
+				for &id in &self.temporary {
+					self.set_copy_edge(view, range, owned, id);
+				}
+			}
+
+			start = end;
+		}
+	}
+
+	fn set_duplicated_tail<N: View>(&mut self, view: &mut N, head: usize, budget: usize) -> bool {
+		let Some(merge) = self.find_merge(view) else {
+			return false;
+		};
+
+		if !self.find_region(view, merge) || !self.find_region_edges(view, head) {
+			return false;
+		}
+
+		// Nothing is changed unless every shared node can be copied within the budget.
+		let cost = self.find_shared(view);
+
+		if self.shared.is_empty() || cost >= budget || !Duplicator::can_run(view, &self.shared) {
+			return false;
+		}
+
+		self.set_shared_copies(view);
+		self.cost = cost;
+
+		true
+	}
+
+	// Post-dominators found for the whole region before it was restructured are reused
+	// when they know of the head. If the arms were merged before the continuation by an
+	// outer branch, the only node of the set leading to it is taken instead.
+	fn find_known_post_dominator<N: Predecessors>(
+		view: &N,
+		post_dominators: &DominatorFinder,
+		head: usize,
+		set: Slice,
+	) -> Option<usize> {
+		let continuation = post_dominators.immediate(head)?;
+
+		if set.contains(continuation) {
+			return Some(continuation);
+		}
+
+		let mut predecessors = view
+			.predecessors(continuation)
+			.filter(|&id| set.contains(id));
+
+		if let (Some(id), None) = (predecessors.next(), predecessors.next()) {
+			Some(id)
+		} else {
+			None
+		}
+	}
+
+	// Post-dominators are otherwise found from the only node of the set without successors
+	// in it, which leads to where the set continues, if anything. The head is only added
+	// to the set while they are found, instead of copying it.
+	fn find_post_dominator<N: View>(
+		&mut self,
+		view: &N,
+		head: usize,
+		set: &mut Set,
+	) -> Option<usize> {
+		let exit = {
+			let set = set.as_slice();
+			let mut exits = set
+				.into_iter()
+				.filter(|&id| !view.successors(id).any(|id| set.contains(id)));
+
+			let (Some(exit), None) = (exits.next(), exits.next()) else {
+				return None;
+			};
+
+			exit
+		};
+
+		set.grow_insert(head);
+
+		self.dominator_finder
+			.run(&Inverted(view), set.as_slice(), exit);
+
+		set.remove(head);
+
+		self.dominator_finder
+			.immediate(head)
+			.filter(|&id| set.contains(id))
+	}
+
+	// An arm is only entered from the head and only leaves to the continuation,
+	// which it must reach.
+	fn is_arm_of<N: Predecessors + Successors>(
+		view: &N,
+		arm: &Set,
+		nodes: &[usize],
+		head: usize,
+		start: usize,
+		continuation: usize,
+	) -> bool {
+		let mut is_merged = false;
+
+		nodes.iter().all(|&id| {
+			is_merged |= view.successors(id).any(|id| id == continuation);
+
+			view.predecessors(id).all(|predecessor| {
+				arm.contains(predecessor) || (id == start && predecessor == head)
+			}) && view
+				.successors(id)
+				.all(|successor| arm.contains(successor) || successor == continuation)
+		}) && is_merged
+	}
+
+	fn find_post_dominated_arms<N: View>(
+		&mut self,
+		view: &N,
+		head: usize,
+		continuation: usize,
+		pool: &mut Vec<Set>,
+	) -> bool {
+		for start in view.successors(head) {
+			if start == continuation {
+				continue;
+			}
+
+			let branch = self.find_branch_successors(view, start, pool);
+			let is_arm = Self::is_arm_of(view, &branch, &self.temporary, head, start, continuation);
+
+			self.searched.extend_from_slice(&self.temporary);
+			self.branches.push((branch, start));
+
+			if !is_arm {
+				return false;
+			}
+		}
+
+		true
+	}
+
+	fn is_merged_at<N: Predecessors>(&self, view: &N, head: usize, continuation: usize) -> bool {
+		view.predecessors(continuation).all(|id| {
+			id == head
+				|| self.tail.contains(id)
+				|| self.branches.iter().any(|branch| branch.0.contains(id))
+		})
+	}
+
+	// Each arm is everything reached from a successor of the head before the continuation,
+	// and the tail is the rest of the set, which is searched in place and keeps its storage.
+	// Nodes reached by an earlier arm are not searched again, so arms meeting before the
+	// continuation are caught as they enter each other. The set is restored on failure.
+	fn find_post_dominated_destinations<N: View>(
+		&mut self,
+		view: &N,
+		head: usize,
+		continuation: usize,
+		set: &mut Set,
+		pool: &mut Vec<Set>,
+	) -> bool {
+		self.retain_branches_if(pool, |_| false);
+		self.searched.clear();
+
+		let nodes = self.depth_first_searcher.nodes_mut();
+
+		std::mem::swap(nodes, set);
+		nodes.remove(continuation);
+
+		let is_arms = self.find_post_dominated_arms(view, head, continuation, pool);
+		let nodes = self.depth_first_searcher.nodes_mut();
+
+		nodes.grow_insert(continuation);
+
+		std::mem::swap(&mut self.tail, nodes);
+
+		self.continuations.clear();
+		self.continuations.push(continuation);
+
+		if is_arms
+			&& self.is_merged_at(view, head, continuation)
+			&& !self.has_orphan_assignments(view)
+		{
+			return true;
+		}
+
+		self.tail.extend(self.searched.iter().copied());
+
+		std::mem::swap(&mut self.tail, set);
+
+		false
+	}
+
+	fn find_structure<N: View>(
+		&mut self,
+		view: &mut N,
+		head: usize,
+		set: &mut Set,
+		post_dominators: Option<&DominatorFinder>,
+		pool: &mut Vec<Set>,
+	) {
+		let known = post_dominators.and_then(|post_dominators| {
+			Self::find_known_post_dominator(view, post_dominators, head, set.as_slice())
+		});
+
+		if let Some(continuation) = known {
+			if self.find_post_dominated_destinations(view, head, continuation, set, pool) {
+				return;
+			}
+		}
+
+		let found = self
+			.find_post_dominator(view, head, set)
+			.filter(|&id| Some(id) != known);
+
+		if let Some(continuation) = found {
+			if self.find_post_dominated_destinations(view, head, continuation, set, pool) {
+				return;
+			}
+		}
+
+		self.depth_first_searcher.nodes_mut().clone_from(set);
+
+		self.find_destinations(view, head, pool);
+		self.set_exits_in_tail(view, head, set, pool);
+		self.find_continuations(view);
+		self.trim_orphans_if_needed(view, pool);
+	}
+
 	fn find_set_of(branches: &mut [(Set, usize)], id: usize) -> Option<&mut Set> {
 		branches
 			.iter_mut()
@@ -238,7 +815,44 @@ impl Single {
 		}
 	}
 
+	// Nodes of the arms that reach the continuation are found in a single search back
+	// from it, as arms are only left through it.
+	fn find_reaching<N: Predecessors>(&mut self, view: &N, continuation: usize) {
+		let nodes = self.depth_first_searcher.nodes_mut();
+
+		nodes.clear();
+
+		for (set, _) in &self.branches {
+			nodes.extend(set.ascending());
+		}
+
+		nodes.grow_insert(continuation);
+
+		self.reaching.clear();
+		self.depth_first_searcher
+			.run(&Inverted(view), continuation, |id, post| {
+				if !post {
+					self.reaching.grow_insert(id);
+				}
+			});
+	}
+
+	// An arm must leave from a single node going nowhere else, which is not so when
+	// the only node leaving also leads to others that repeat forever without it.
+	fn has_endless_successor<N: Successors>(
+		view: &N,
+		set: &Set,
+		reaching: &Set,
+		id: usize,
+	) -> bool {
+		view.successors(id)
+			.any(|successor| set.contains(successor) && !reaching.contains(successor))
+	}
+
+	// Arms that repeat forever have no edges to the continuation and are left as they are.
 	fn set_continuation_merges<N: View>(&mut self, view: &mut N, continuation: usize) {
+		self.find_reaching(view, continuation);
+
 		for (set, _) in &mut self.branches {
 			self.temporary.clear();
 			self.temporary.extend(
@@ -246,7 +860,13 @@ impl Single {
 					.filter(|&id| set.contains(id)),
 			);
 
-			if self.temporary.len() > 1 {
+			let is_shared = match *self.temporary.as_slice() {
+				[] => false,
+				[id] => Self::has_endless_successor(view, set, &self.reaching, id),
+				_ => true,
+			};
+
+			if is_shared {
 				let dummy = view.add_no_operation();
 
 				for &predecessor in &self.temporary {
@@ -293,31 +913,267 @@ impl Single {
 		}
 	}
 
-	/// Applies the restructuring algorithm to the given set of nodes starting at the head.
-	/// The end node of the structured branch is returned, if applicable.
+	// The continuation is the immediate post-dominator of the head if the head dominates
+	// it. If the head post-dominator is instead where the region leads, the only node of
+	// the region leading there is taken, which may have been added when merging its arms.
+	fn find_dominated_continuation<N: Predecessors>(
+		view: &N,
+		head: usize,
+		exit: Option<usize>,
+		post: Option<usize>,
+		dominance: &Dominance,
+	) -> Option<usize> {
+		let continuation = dominance.post_dominators.immediate(head)?;
+
+		if dominance.dominators.dominates(head, continuation) {
+			return Some(continuation);
+		}
+
+		let exit = exit.filter(|&exit| exit != head && Some(continuation) == post)?;
+
+		view.predecessors(exit)
+			.all(|id| id == head || dominance.dominators.dominates(head, id))
+			.then_some(exit)
+	}
+
+	// Every successor of the head other than the continuation must only be entered from
+	// it, so that its arm is exactly its subtree in the dominator tree.
+	fn find_dominated_arms<N: Predecessors + Successors>(
+		&mut self,
+		view: &N,
+		head: usize,
+		continuation: usize,
+		dominance: &Dominance,
+	) -> bool {
+		self.arms.clear();
+		self.sorted.clear();
+
+		for start in view.successors(head) {
+			if start == continuation {
+				continue;
+			}
+
+			let mut predecessors = view.predecessors(start);
+
+			if predecessors.next() != Some(head) || predecessors.next().is_some() {
+				return false;
+			}
+
+			let Some(number) = dominance.dominators.tree_number(start) else {
+				return false;
+			};
+
+			self.sorted.push((number, self.arms.len()));
+			self.arms.push((start, 0));
+		}
+
+		self.sorted.sort_unstable();
+
+		true
+	}
+
+	fn find_arm_of(&self, id: usize, dominance: &Dominance) -> Option<usize> {
+		let number = dominance.dominators.tree_number(id)?;
+		let position = self.sorted.partition_point(|&(start, _)| start <= number);
+		let (_, index) = *self.sorted.get(position.checked_sub(1)?)?;
+
+		dominance
+			.dominators
+			.dominates(self.arms[index].0, id)
+			.then_some(index)
+	}
+
+	// The edges into the continuation are grouped by arm, and an arm leaves only to the
+	// continuation when all the edges leaving its subtree are among them. The rest must
+	// come from the tail, and assignments must not be split between the arms and it.
+	fn find_dominated_merges<N: View>(
+		&mut self,
+		view: &N,
+		head: usize,
+		continuation: usize,
+		dominance: &Dominance,
+	) -> bool {
+		let mut has_in_tail = false;
+		let mut has_in_branch = false;
+
+		self.merged.clear();
+
+		for id in view.predecessors(continuation) {
+			let is_assigned = view.has_assignment(id, Flag::A);
+
+			if id == head {
+				has_in_branch |= is_assigned;
+			} else if let Some(index) = self.find_arm_of(id, dominance) {
+				has_in_branch |= is_assigned;
+
+				self.merged.push((index, id));
+				self.arms[index].1 += 1;
+			} else if dominance.dominators.dominates(continuation, id) {
+				has_in_tail |= is_assigned;
+			} else {
+				return false;
+			}
+		}
+
+		// Sorting is stable, so edges keep their order within each arm.
+		self.merged.sort_by_key(|&(index, _)| index);
+
+		!(has_in_tail && has_in_branch)
+			&& self
+				.arms
+				.iter()
+				.all(|&(start, count)| count != 0 && count == dominance.leaving[start])
+	}
+
+	// As in `has_endless_successor`, but repetitions are normalized so a successor
+	// reaches the continuation through the node exactly when it dominates the node.
+	fn has_dominated_endless_successor<N: Successors>(
+		view: &N,
+		start: usize,
+		id: usize,
+		dominance: &Dominance,
+	) -> bool {
+		view.successors(id)
+			.filter(|&successor| dominance.dominators.dominates(start, successor))
+			.any(|successor| !dominance.dominators.dominates(successor, id))
+	}
+
+	fn set_dominated_merges<N: View>(
+		&mut self,
+		view: &mut N,
+		continuation: usize,
+		dominance: &Dominance,
+	) {
+		let mut merged = self.merged.as_slice();
+
+		for (index, arm) in self.arms.iter_mut().enumerate() {
+			let len = merged.partition_point(|&(arm, _)| arm == index);
+			let (edges, rest) = merged.split_at(len);
+
+			merged = rest;
+
+			// Every arm was found to leave to the continuation, so it has edges to it.
+			let is_shared = match *edges {
+				[(_, id)] => Self::has_dominated_endless_successor(view, arm.0, id, dominance),
+				_ => true,
+			};
+
+			if is_shared {
+				let dummy = view.add_no_operation();
+
+				for &(_, predecessor) in edges {
+					view.replace_edge(predecessor, continuation, dummy);
+				}
+
+				view.add_edge(dummy, continuation);
+
+				self.additional.push(dummy);
+
+				arm.1 = dummy;
+			} else {
+				arm.1 = edges[0].1;
+			}
+		}
+	}
+
+	fn fill_dominated_branches<N: View>(&mut self, view: &mut N, head: usize, continuation: usize) {
+		self.temporary.clear();
+		self.temporary.extend(view.successors(head));
+
+		for &id in &self.temporary {
+			if id != continuation {
+				continue;
+			}
+
+			let dummy = view.add_no_operation();
+
+			view.replace_edge(head, id, dummy);
+			view.add_edge(dummy, id);
+
+			self.additional.push(dummy);
+		}
+	}
+
+	/// Applies the restructuring algorithm to the head of a region that is the subtree of
+	/// the dominator tree at one of its nodes, along with the node leaving it, if any, and
+	/// the post-dominator of the region it leads to. This succeeds when the branch meets
+	/// again where the post-dominators say, in which case its arms are the subtrees of the
+	/// successors of the head and are never searched. It takes time proportional to the
+	/// edges of the head and of the continuation, which is returned. Otherwise, `None` is
+	/// returned and nothing is changed.
+	pub fn run_dominated<N: View>(
+		&mut self,
+		view: &mut N,
+		head: usize,
+		exit: Option<usize>,
+		post: Option<usize>,
+		dominance: &Dominance,
+	) -> Option<usize> {
+		self.additional.clear();
+		self.duplicates.clear();
+		self.cost = 0;
+
+		let continuation = Self::find_dominated_continuation(view, head, exit, post, dominance)?;
+
+		if !self.find_dominated_arms(view, head, continuation, dominance)
+			|| !self.find_dominated_merges(view, head, continuation, dominance)
+		{
+			return None;
+		}
+
+		self.set_dominated_merges(view, continuation, dominance);
+		self.fill_dominated_branches(view, head, continuation);
+
+		Some(continuation)
+	}
+
+	/// Applies the restructuring algorithm to the given set of nodes starting at the head,
+	/// duplicating nodes only when their cost is under the budget. Post-dominators found
+	/// for an enclosing region are used when given, and checked before being relied on.
+	/// The set may be reused for the tail, so its contents are unspecified afterwards.
+	/// The end node of the structured branch is returned, if its arms meet again.
 	pub fn run<N: View>(
 		&mut self,
 		view: &mut N,
 		head: usize,
-		set: Slice,
+		set: &mut Set,
+		post_dominators: Option<&DominatorFinder>,
 		pool: &mut Vec<Set>,
-	) -> usize {
-		self.depth_first_searcher.nodes_mut().clone_from_slice(set);
+		budget: usize,
+	) -> Option<usize> {
+		self.additional.clear();
+		self.duplicates.clear();
+		self.cost = 0;
 
-		self.find_destinations(view, head, pool);
-		self.find_continuations(view);
-		self.trim_orphans_if_needed(view, pool);
+		self.find_structure(view, head, set, post_dominators, pool);
 
-		let continuation = if let &[continuation] = self.continuations.as_slice() {
-			continuation
-		} else {
-			self.set_new_continuation(view, head)
+		if self.continuations.len() > 1
+			&& budget != 0
+			&& self.set_duplicated_tail(view, head, budget)
+		{
+			let mut nodes = pool.pop().unwrap_or_default();
+
+			nodes.clone_from(set);
+			nodes.extend(self.duplicates.iter().copied());
+
+			self.additional.extend_from_slice(&self.duplicates);
+			self.find_structure(view, head, &mut nodes, post_dominators, pool);
+
+			pool.push(nodes);
+		}
+
+		// Arms that never meet, such as those ending in repetitions without exits,
+		// are left as they are.
+		let continuation = match *self.continuations.as_slice() {
+			[] => return None,
+			[continuation] => continuation,
+			_ => self.set_new_continuation(view, head),
 		};
 
 		self.set_continuation_merges(view, continuation);
 		self.fill_empty_branches(view, head);
 
-		continuation
+		Some(continuation)
 	}
 }
 
@@ -0,0 +1,280 @@
+use std::ops::ControlFlow;
+
+use crate::{
+	interpreter::{Fault, Interpreter},
+	view::{Classify, Kind, Successors},
+};
+
+/// A description of the first decision sequence where the graphs disagree.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Mismatch {
+	/// The graphs visited different original nodes.
+	Trace {
+		decisions: Vec<usize>,
+		before: Vec<usize>,
+		after: Vec<usize>,
+	},
+
+	/// The graphs visited the same original nodes, but only one of them left the
+	/// graph or they left it from different exits.
+	Exit {
+		decisions: Vec<usize>,
+		before: Option<usize>,
+		after: Option<usize>,
+	},
+
+	/// One of the graphs could not be executed, which is the graph after restructuring
+	/// if `restructured` is set.
+	Fault {
+		decisions: Vec<usize>,
+		fault: Fault,
+		restructured: bool,
+	},
+}
+
+impl std::fmt::Display for Mismatch {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Trace {
+				decisions,
+				before,
+				after,
+			} => write!(
+				f,
+				"decisions {decisions:?} visited {before:?} before and {after:?} after"
+			),
+			Self::Exit {
+				decisions,
+				before,
+				after,
+			} => write!(
+				f,
+				"decisions {decisions:?} left at {before:?} before and {after:?} after"
+			),
+			Self::Fault {
+				decisions,
+				fault,
+				restructured,
+			} => {
+				let graph = if *restructured { "after" } else { "before" };
+
+				write!(f, "decisions {decisions:?} faulted {graph}, {fault}")
+			}
+		}
+	}
+}
+
+impl std::error::Error for Mismatch {}
+
+/// This structure checks that a graph behaves the same before and after being
+/// restructured. It enumerates the decisions taken at original nodes, in order,
+/// and confirms both graphs visit the same original nodes for each sequence of
+/// them and leave from the same exit. Duplicated nodes count as their origin, and
+/// synthetic exits count as the original node they were reached from.
+pub struct Equivalence {
+	decisions: Vec<(usize, usize)>,
+	before: Vec<usize>,
+	after: Vec<usize>,
+
+	depth: usize,
+	runs: usize,
+
+	interpreter: Interpreter,
+}
+
+impl Equivalence {
+	/// Creates a new instance of the checker.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			decisions: Vec::new(),
+			before: Vec::new(),
+			after: Vec::new(),
+
+			depth: 64,
+			runs: 1024,
+
+			interpreter: Interpreter::new(),
+		}
+	}
+
+	/// Sets the maximum number of original nodes visited in a single run.
+	pub fn set_depth(&mut self, depth: usize) {
+		self.depth = depth;
+	}
+
+	/// Sets the maximum number of decision sequences tried.
+	pub fn set_runs(&mut self, runs: usize) {
+		self.runs = runs;
+	}
+
+	/// Returns the interpreter used, so that its limits can be changed.
+	#[must_use]
+	pub fn interpreter_mut(&mut self) -> &mut Interpreter {
+		&mut self.interpreter
+	}
+
+	fn find_decisions(&self) -> Vec<usize> {
+		self.decisions.iter().map(|&(index, _)| index).collect()
+	}
+
+	// The first run to reach a decision records it, with its number of choices. The exit
+	// left from is returned, or `None` if the run was stopped before reaching one.
+	fn run_trace<N>(
+		&mut self,
+		view: &N,
+		start: usize,
+		trace: &mut Vec<usize>,
+	) -> Result<Option<usize>, Fault>
+	where
+		N: Successors + Classify,
+	{
+		let decisions = &mut self.decisions;
+		let depth = self.depth;
+		let mut next = 0;
+		let mut is_stopped = false;
+
+		trace.clear();
+
+		let exit = self.interpreter.run(
+			view,
+			start,
+			|_, count| {
+				if next == decisions.len() {
+					decisions.push((0, count));
+				}
+
+				next += 1;
+
+				decisions[next - 1].0
+			},
+			|id| {
+				if view.kind(id) == Kind::Original {
+					trace.push(view.origin(id));
+				}
+
+				if trace.len() < depth {
+					ControlFlow::Continue(())
+				} else {
+					is_stopped = true;
+
+					ControlFlow::Break(())
+				}
+			},
+		)?;
+
+		if is_stopped {
+			Ok(None)
+		} else if view.kind(exit) == Kind::Original {
+			Ok(Some(view.origin(exit)))
+		} else {
+			Ok(trace.last().copied())
+		}
+	}
+
+	// Decisions are advanced like an odometer, with the last one changing first.
+	fn advance_decisions(&mut self) -> bool {
+		while let Some((index, count)) = self.decisions.last_mut() {
+			*index += 1;
+
+			if index < count {
+				return true;
+			}
+
+			self.decisions.pop();
+		}
+
+		false
+	}
+
+	fn map_fault(
+		&self,
+		result: Result<Option<usize>, Fault>,
+		restructured: bool,
+	) -> Result<Option<usize>, Mismatch> {
+		result.map_err(|fault| Mismatch::Fault {
+			decisions: self.find_decisions(),
+			fault,
+			restructured,
+		})
+	}
+
+	fn check_trace<A, B>(
+		&mut self,
+		before: &A,
+		before_start: usize,
+		after: &B,
+		after_start: usize,
+	) -> Result<(), Mismatch>
+	where
+		A: Successors + Classify,
+		B: Successors + Classify,
+	{
+		let mut trace = std::mem::take(&mut self.before);
+		let result = self.run_trace(before, before_start, &mut trace);
+
+		self.before = trace;
+
+		let before_exit = self.map_fault(result, false)?;
+
+		let mut trace = std::mem::take(&mut self.after);
+		let result = self.run_trace(after, after_start, &mut trace);
+
+		self.after = trace;
+
+		let after_exit = self.map_fault(result, true)?;
+
+		if self.before != self.after {
+			Err(Mismatch::Trace {
+				decisions: self.find_decisions(),
+				before: self.before.clone(),
+				after: self.after.clone(),
+			})
+		} else if before_exit != after_exit {
+			Err(Mismatch::Exit {
+				decisions: self.find_decisions(),
+				before: before_exit,
+				after: after_exit,
+			})
+		} else {
+			Ok(())
+		}
+	}
+
+	/// Checks that the graph after restructuring visits the same original nodes
+	/// and leaves from the same exit as the graph before it, for every sequence of
+	/// decisions tried.
+	///
+	/// # Errors
+	///
+	/// Returns a description of the first sequence of decisions that disagrees.
+	pub fn run<A, B>(
+		&mut self,
+		before: &A,
+		before_start: usize,
+		after: &B,
+		after_start: usize,
+	) -> Result<(), Mismatch>
+	where
+		A: Successors + Classify,
+		B: Successors + Classify,
+	{
+		self.decisions.clear();
+
+		for _ in 0..self.runs {
+			self.check_trace(before, before_start, after, after_start)?;
+
+			if !self.advance_decisions() {
+				break;
+			}
+		}
+
+		Ok(())
+	}
+}
+
+impl Default for Equivalence {
+	fn default() -> Self {
+		Self::new()
+	}
+}
@@ -0,0 +1,45 @@
+use crate::verify::Violation;
+
+/// A description of the first precondition found broken before restructuring.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum StructureError {
+	/// The start node is not in the set.
+	Outside { start: usize },
+
+	/// A repetition has no edge coming into it from outside, so it is never entered.
+	Unentered { region: Vec<usize> },
+
+	/// The set has more than one node without successors in it.
+	Exits { exits: Vec<usize> },
+
+	/// A repetition was not normalized, such as by running [`Repeat`] first.
+	///
+	/// [`Repeat`]: crate::repeat::Repeat
+	Unnormalized(Violation),
+}
+
+impl std::fmt::Display for StructureError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Outside { start } => write!(f, "start {start} is not in the set"),
+			Self::Unentered { region } => write!(f, "repetition {region:?} is never entered"),
+			Self::Exits { exits } => write!(f, "set has exits {exits:?} instead of at most one"),
+			Self::Unnormalized(violation) => write!(f, "set is not normalized, {violation}"),
+		}
+	}
+}
+
+impl std::error::Error for StructureError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Self::Unnormalized(violation) => Some(violation),
+			_ => None,
+		}
+	}
+}
+
+impl From<Violation> for StructureError {
+	fn from(violation: Violation) -> Self {
+		Self::Unnormalized(violation)
+	}
+}
@@ -0,0 +1,140 @@
+use std::ops::ControlFlow;
+
+use crate::view::{Classify, Flag, Kind, Successors};
+
+/// An error found while executing a graph.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Fault {
+	/// The step limit was reached at the node before any exit was.
+	Exhausted { node: usize },
+
+	/// A selection read a flag that was never assigned.
+	Unassigned { node: usize, flag: Flag },
+
+	/// A node chose a successor that it does not have.
+	Missing { node: usize, index: usize },
+}
+
+impl std::fmt::Display for Fault {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Exhausted { node } => write!(f, "ran out of steps at node {node}"),
+			Self::Unassigned { node, flag } => {
+				write!(f, "node {node} selected on unassigned flag {flag:?}")
+			}
+			Self::Missing { node, index } => write!(f, "node {node} has no successor {index}"),
+		}
+	}
+}
+
+impl std::error::Error for Fault {}
+
+/// This structure executes a graph from a start node. Assignments store into the
+/// flag registers and selections go to the successor at the index held by their
+/// flag. Original nodes with more than one successor ask an oracle which one to take.
+/// Execution ends at a node without successors.
+pub struct Interpreter {
+	registers: [Option<usize>; 3],
+	fuel: usize,
+}
+
+impl Interpreter {
+	/// Creates a new instance of the interpreter.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			registers: [None; 3],
+			fuel: 1 << 16,
+		}
+	}
+
+	/// Returns the value last assigned to the flag, if any.
+	#[must_use]
+	pub const fn flag(&self, flag: Flag) -> Option<usize> {
+		self.registers[flag as usize]
+	}
+
+	/// Sets the maximum number of nodes executed in a single run.
+	pub fn set_fuel(&mut self, fuel: usize) {
+		self.fuel = fuel;
+	}
+
+	fn find_index<N, O>(&mut self, view: &N, id: usize, oracle: &mut O) -> Result<usize, Fault>
+	where
+		N: Successors + Classify,
+		O: FnMut(usize, usize) -> usize,
+	{
+		let index = match view.kind(id) {
+			Kind::Original => {
+				let count = view.successors(id).count();
+
+				if count > 1 {
+					oracle(id, count)
+				} else {
+					0
+				}
+			}
+			Kind::NoOperation => 0,
+			Kind::Selection(flag) => {
+				self.registers[flag as usize].ok_or(Fault::Unassigned { node: id, flag })?
+			}
+			Kind::Assignment(flag, value) => {
+				self.registers[flag as usize] = Some(value);
+
+				0
+			}
+		};
+
+		Ok(index)
+	}
+
+	/// Executes the graph from the start node. The oracle is given an original node and
+	/// its number of successors and returns the index of the one to take. The handler is
+	/// given every node before it is executed and may break to stop early.
+	/// The last node executed is returned, which is either an exit or where we stopped.
+	///
+	/// # Errors
+	///
+	/// Returns a fault if the graph can not be executed any further.
+	pub fn run<N, O, H>(
+		&mut self,
+		view: &N,
+		start: usize,
+		mut oracle: O,
+		mut handler: H,
+	) -> Result<usize, Fault>
+	where
+		N: Successors + Classify,
+		O: FnMut(usize, usize) -> usize,
+		H: FnMut(usize) -> ControlFlow<()>,
+	{
+		let mut id = start;
+
+		self.registers = [None; 3];
+
+		for _ in 0..self.fuel {
+			if handler(id).is_break() {
+				return Ok(id);
+			}
+
+			let index = self.find_index(view, id, &mut oracle)?;
+			let mut successors = view.successors(id);
+
+			if let Some(successor) = successors.nth(index) {
+				id = successor;
+			} else if index == 0 && !matches!(view.kind(id), Kind::Selection(_)) {
+				return Ok(id);
+			} else {
+				return Err(Fault::Missing { node: id, index });
+			}
+		}
+
+		Err(Fault::Exhausted { node: id })
+	}
+}
+
+impl Default for Interpreter {
+	fn default() -> Self {
+		Self::new()
+	}
+}
@@ -0,0 +1,350 @@
+// Resources:
+// "Beyond Relooper: Recursive Translation of Unstructured Control Flow to Structured Control Flow",
+//     by Norman Ramsey
+
+use crate::{
+	pass::dominator_finder::DominatorFinder,
+	repeat::Repeat,
+	set::Set,
+	view::{Predecessors, Successors, View},
+};
+
+/// A node of the labeled control tree.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Node {
+	/// A single node of the graph.
+	Simple(usize),
+
+	/// A list of structures executed one after the other.
+	Sequence(Vec<Node>),
+
+	/// A head selecting one of the arms, which do not meet again.
+	Select { head: usize, arms: Vec<Node> },
+
+	/// A body that a break leaves, continuing after the block.
+	Block(Box<Node>),
+
+	/// A body that a break repeats, continuing at the start of the loop.
+	Loop(Box<Node>),
+
+	/// Breaks out to the block or loop at the given depth, counting outwards
+	/// from `0` over the blocks and loops around it.
+	Break(usize),
+
+	/// Leaves the graph after a node without successors.
+	Return,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Label {
+	Loop(usize),
+	Block(usize),
+}
+
+// The steps left to build the tree, each adding to the last list being built.
+#[derive(Clone, Copy)]
+enum Task {
+	Tree(usize),
+	Exits(usize),
+	Arm { source: usize, target: usize },
+	Merge(usize),
+	EndArm,
+	EndSelect(usize),
+	EndWithin,
+	EndLoop,
+}
+
+/// This structure builds a labeled control tree out of a graph, in the style of
+/// "Beyond Relooper". Exits of reducible graphs are expressed as breaks to the
+/// blocks and loops of the tree, so flags are only brought in by running [`Repeat`]
+/// on repetitions with many entries when the graph is irreducible.
+pub struct Builder {
+	children: Vec<(usize, usize)>,
+	labels: Vec<Label>,
+	tasks: Vec<Task>,
+	lists: Vec<Vec<Node>>,
+	targets: Vec<usize>,
+
+	repeat: Repeat,
+	dominator_finder: DominatorFinder,
+}
+
+impl Builder {
+	/// Creates a new instance of the builder.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			children: Vec::new(),
+			labels: Vec::new(),
+			tasks: Vec::new(),
+			lists: Vec::new(),
+			targets: Vec::new(),
+
+			repeat: Repeat::new(),
+			dominator_finder: DominatorFinder::new(),
+		}
+	}
+
+	fn position(&self, id: usize) -> usize {
+		self.dominator_finder
+			.reverse_post_number(id)
+			.unwrap_or(usize::MAX)
+	}
+
+	// Dominators are found again as `Repeat` may add nodes, and the children of each
+	// node in the dominator tree are kept sorted by their reverse post-order.
+	fn find_dominators<N>(&mut self, view: &N, set: &Set, start: usize)
+	where
+		N: Predecessors + Successors,
+	{
+		self.dominator_finder.run(view, set.as_slice(), start);
+		self.children.clear();
+
+		for id in self.dominator_finder.reverse_post_order() {
+			if let Some(dominator) = self.dominator_finder.immediate(id) {
+				self.children.push((dominator, id));
+			}
+		}
+
+		let dominator_finder = &self.dominator_finder;
+
+		self.children.sort_unstable_by_key(|&(dominator, id)| {
+			(dominator, dominator_finder.reverse_post_number(id))
+		});
+	}
+
+	// Nodes reachable from the start are exactly those numbered, so an edge from one
+	// of them to a node outside of the set leaves it.
+	fn has_edge_leaving<N: Successors>(&self, view: &N, set: &Set) -> bool {
+		self.dominator_finder
+			.reverse_post_order()
+			.any(|id| view.successors(id).any(|id| !set.contains(id)))
+	}
+
+	// An edge going back to an earlier node that does not dominate its source enters
+	// a repetition somewhere other than its start.
+	fn is_irreducible<N: Successors>(&self, view: &N) -> bool {
+		self.dominator_finder.reverse_post_order().any(|id| {
+			view.successors(id).any(|successor| {
+				self.position(successor) <= self.position(id)
+					&& !self.dominator_finder.dominates(successor, id)
+			})
+		})
+	}
+
+	fn is_loop_header<N: Predecessors>(&self, view: &N, id: usize) -> bool {
+		let position = self.position(id);
+
+		view.predecessors(id)
+			.any(|id| self.position(id) != usize::MAX && self.position(id) >= position)
+	}
+
+	fn is_merge<N: Predecessors>(&self, view: &N, id: usize) -> bool {
+		let position = self.position(id);
+
+		view.predecessors(id)
+			.filter(|&id| self.position(id) < position)
+			.nth(1)
+			.is_some()
+	}
+
+	// Merge nodes are ordered with the latest first, as it is followed by the
+	// outermost block.
+	fn find_merge_children<N: Predecessors>(&mut self, view: &N, id: usize) {
+		let first = self.children.partition_point(|child| child.0 < id);
+		let last = self.children.partition_point(|child| child.0 <= id);
+
+		self.targets.clear();
+
+		for &(_, child) in self.children[first..last].iter().rev() {
+			if self.is_merge(view, child) {
+				self.targets.push(child);
+			}
+		}
+	}
+
+	fn find_depth(&self, label: Label) -> Option<usize> {
+		self.labels.iter().rev().position(|&other| other == label)
+	}
+
+	fn list_mut(&mut self) -> &mut Vec<Node> {
+		self.lists.last_mut().unwrap()
+	}
+
+	fn add_branch<N>(&mut self, view: &N, source: usize, target: usize) -> Option<()>
+	where
+		N: Predecessors + Successors,
+	{
+		let position = self.position(target);
+
+		if position == usize::MAX {
+			return None;
+		} else if position <= self.position(source) {
+			let depth = self.find_depth(Label::Loop(target))?;
+
+			self.list_mut().push(Node::Break(depth));
+		} else if self.is_merge(view, target) {
+			let depth = self.find_depth(Label::Block(target))?;
+
+			self.list_mut().push(Node::Break(depth));
+		} else {
+			self.tasks.push(Task::Tree(target));
+		}
+
+		Some(())
+	}
+
+	fn add_exits<N>(&mut self, view: &N, id: usize) -> Option<()>
+	where
+		N: Predecessors + Successors,
+	{
+		let mut successors = view.successors(id);
+
+		match (successors.next(), successors.next()) {
+			(None, _) => {
+				let list = self.list_mut();
+
+				list.push(Node::Simple(id));
+				list.push(Node::Return);
+			}
+			(Some(successor), None) => {
+				self.list_mut().push(Node::Simple(id));
+
+				return self.add_branch(view, id, successor);
+			}
+			_ => {
+				self.targets.clear();
+				self.targets.extend(view.successors(id));
+				self.lists.push(Vec::new());
+				self.tasks.push(Task::EndSelect(id));
+
+				for &target in self.targets.iter().rev() {
+					self.tasks.push(Task::EndArm);
+					self.tasks.push(Task::Arm { source: id, target });
+				}
+			}
+		}
+
+		Some(())
+	}
+
+	// Each merge node dominated by this one follows a block around the code before
+	// it, so that it can be reached by breaking out of the block.
+	fn add_within<N: Predecessors>(&mut self, view: &N, id: usize) {
+		self.find_merge_children(view, id);
+		self.labels
+			.extend(self.targets.iter().map(|&id| Label::Block(id)));
+
+		self.lists.push(Vec::new());
+		self.tasks.push(Task::EndWithin);
+		self.tasks
+			.extend(self.targets.iter().map(|&id| Task::Merge(id)));
+		self.tasks.push(Task::Exits(id));
+	}
+
+	fn add_tree<N: Predecessors>(&mut self, view: &N, id: usize) {
+		if self.is_loop_header(view, id) {
+			self.labels.push(Label::Loop(id));
+			self.lists.push(Vec::new());
+			self.tasks.push(Task::EndLoop);
+		}
+
+		self.add_within(view, id);
+	}
+
+	fn run_task<N>(&mut self, view: &N, task: Task) -> Option<()>
+	where
+		N: Predecessors + Successors,
+	{
+		match task {
+			Task::Tree(id) => self.add_tree(view, id),
+			Task::Exits(id) => return self.add_exits(view, id),
+			Task::Arm { source, target } => {
+				self.lists.push(Vec::new());
+
+				return self.add_branch(view, source, target);
+			}
+			Task::Merge(id) => {
+				let inner = self.lists.pop().unwrap();
+
+				self.labels.pop();
+				self.lists
+					.push(vec![Node::Block(Box::new(Node::Sequence(inner)))]);
+				self.add_tree(view, id);
+			}
+			Task::EndArm => {
+				let arm = self.lists.pop().unwrap();
+
+				self.list_mut().push(Node::Sequence(arm));
+			}
+			Task::EndSelect(head) => {
+				let arms = self.lists.pop().unwrap();
+
+				self.list_mut().push(Node::Select { head, arms });
+			}
+			Task::EndWithin => {
+				let inner = self.lists.pop().unwrap();
+
+				self.list_mut().extend(inner);
+			}
+			Task::EndLoop => {
+				let body = self.lists.pop().unwrap();
+
+				self.labels.pop();
+				self.list_mut()
+					.push(Node::Loop(Box::new(Node::Sequence(body))));
+			}
+		}
+
+		Some(())
+	}
+
+	/// Builds the labeled control tree of the set starting at the given node, which
+	/// should hold every node reachable from it. If the set is irreducible, its repetitions
+	/// with many entries are first given a single start by [`Repeat`], which may also add
+	/// a new start before the old one.
+	///
+	/// Returns `None` if an edge leaves the set, as there is nothing in the tree to
+	/// break to or to follow it with, in which case the graph is left unchanged.
+	pub fn run<N: View>(&mut self, view: &mut N, set: &mut Set, mut start: usize) -> Option<Node> {
+		self.find_dominators(view, set, start);
+
+		if self.has_edge_leaving(view, set) {
+			return None;
+		}
+
+		if self.is_irreducible(view) {
+			// The start must be entered from outside of any repetition it is part of.
+			if view.predecessors(start).next().is_some() {
+				let id = view.add_no_operation();
+
+				view.add_edge(id, start);
+				set.grow_insert(id);
+
+				start = id;
+			}
+
+			self.repeat.set_irreducible_only(true);
+			self.repeat.run(view, set);
+			self.find_dominators(view, set, start);
+		}
+
+		self.labels.clear();
+		self.tasks.clear();
+		self.lists.clear();
+		self.lists.push(Vec::new());
+		self.tasks.push(Task::Tree(start));
+
+		while let Some(task) = self.tasks.pop() {
+			self.run_task(view, task)?;
+		}
+
+		self.lists.pop().map(Node::Sequence)
+	}
+}
+
+impl Default for Builder {
+	fn default() -> Self {
+		Self::new()
+	}
+}
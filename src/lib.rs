@@ -4,8 +4,19 @@
 //        Helge Bahmann, Jan Christian Meyer, and Magnus Jahre.
 
 pub mod branch;
+pub mod equivalence;
+pub mod error;
+pub mod interpreter;
+pub mod labeled;
+pub mod list;
 pub mod pass;
+pub mod relooper;
 pub mod repeat;
+pub mod stackifier;
+pub mod structurer;
+pub mod summary;
+pub mod tree;
+pub mod verify;
 pub mod view;
 
 pub use set;
@@ -0,0 +1,321 @@
+use crate::view::{Classify, Flag, Kind, Predecessors, Successors, View};
+
+/// The statement held by a node of a [`List`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Statement<T> {
+	/// A node that does nothing, added to keep the graph structured.
+	NoOperation,
+
+	/// A node of the original graph, holding the user payload.
+	Simple(T),
+
+	/// A node that selects the successor at the index held by the flag.
+	Select { flag: Flag },
+
+	/// A node that assigns a value to the flag.
+	Assign { flag: Flag, value: usize },
+}
+
+impl<T> Statement<T> {
+	const fn group(&self) -> &'static str {
+		match self {
+			Self::NoOperation => "A",
+			Self::Simple(_) => "B",
+			Self::Select { .. } => "C",
+			Self::Assign { .. } => "D",
+		}
+	}
+
+	const fn color(&self) -> &'static str {
+		match self {
+			Self::NoOperation => "#C2C5FA",
+			Self::Simple(_) => "#FBE78E",
+			Self::Select { .. } | Self::Assign { .. } => "#EF8784",
+		}
+	}
+
+	fn label(&self, f: &mut std::fmt::Formatter<'_>, original: &mut usize) -> std::fmt::Result {
+		match self {
+			Self::NoOperation => Ok(()),
+			Self::Simple(_) => {
+				*original += 1;
+
+				write!(f, "S{original}")
+			}
+			Self::Select { flag } => write!(f, "{flag:?}?"),
+			Self::Assign { flag, value } => write!(f, "{flag:?} := {value}"),
+		}
+	}
+}
+
+/// A node of a [`List`], with its statement and edges.
+#[derive(Clone, Debug)]
+pub struct Node<T> {
+	predecessors: Vec<usize>,
+	successors: Vec<usize>,
+	statement: Statement<T>,
+	origin: usize,
+}
+
+impl<T> Node<T> {
+	/// Returns the predecessors of the node.
+	#[must_use]
+	pub fn predecessors(&self) -> &[usize] {
+		&self.predecessors
+	}
+
+	/// Returns the successors of the node, in the order they were added.
+	#[must_use]
+	pub fn successors(&self) -> &[usize] {
+		&self.successors
+	}
+
+	/// Returns the statement of the node.
+	#[must_use]
+	pub const fn statement(&self) -> &Statement<T> {
+		&self.statement
+	}
+
+	/// Returns the node this node was duplicated from, or itself if it was not.
+	#[must_use]
+	pub const fn origin(&self) -> usize {
+		self.origin
+	}
+}
+
+/// A control flow graph stored as an adjacency list, usable as a reference
+/// implementation of [`View`]. Its [`Debug`] output is a DOT digraph.
+#[derive(Clone)]
+pub struct List<T> {
+	nodes: Vec<Node<T>>,
+	copy: Option<fn(&T) -> T>,
+}
+
+impl<T> std::fmt::Debug for List<T> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		writeln!(f, "digraph {{")?;
+		writeln!(f, "\tnode [shape = box, style = filled, ordering = out];")?;
+
+		let mut original = 0;
+
+		for (id, Node { statement, .. }) in self.nodes.iter().enumerate() {
+			write!(f, "\tN{id} [")?;
+			write!(f, "xlabel = {id}, ")?;
+			write!(f, "label = \"")?;
+
+			statement.label(f, &mut original)?;
+
+			write!(f, "\", ")?;
+			write!(f, "group = {}, ", statement.group())?;
+			write!(f, "fillcolor = \"{}\"", statement.color())?;
+			writeln!(f, "];")?;
+		}
+
+		for (id, Node { successors, .. }) in self.nodes.iter().enumerate() {
+			for &successor in successors {
+				writeln!(f, "\tN{id} -> N{successor};")?;
+			}
+		}
+
+		writeln!(f, "}}")
+	}
+}
+
+impl<T> List<T> {
+	/// Creates a new empty graph.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			nodes: Vec::new(),
+			copy: None,
+		}
+	}
+
+	/// Creates a new empty graph with space for the given number of nodes.
+	#[must_use]
+	pub fn with_capacity(capacity: usize) -> Self {
+		let nodes = Vec::with_capacity(capacity);
+
+		Self { nodes, copy: None }
+	}
+
+	/// Returns the number of nodes in the graph.
+	#[must_use]
+	pub fn len(&self) -> usize {
+		self.nodes.len()
+	}
+
+	/// Returns whether the graph has no nodes.
+	#[must_use]
+	pub fn is_empty(&self) -> bool {
+		self.nodes.is_empty()
+	}
+
+	/// Returns the nodes of the graph, indexed by their identifier.
+	#[must_use]
+	pub fn nodes(&self) -> &[Node<T>] {
+		&self.nodes
+	}
+
+	/// Returns the statement of the node.
+	#[must_use]
+	pub fn statement(&self, id: usize) -> &Statement<T> {
+		&self.nodes[id].statement
+	}
+
+	/// Returns the statement of the node mutably.
+	#[must_use]
+	pub fn statement_mut(&mut self, id: usize) -> &mut Statement<T> {
+		&mut self.nodes[id].statement
+	}
+
+	fn add_node(&mut self, statement: Statement<T>, origin: usize) -> usize {
+		let node = Node {
+			predecessors: Vec::new(),
+			successors: Vec::new(),
+			statement,
+			origin,
+		};
+
+		self.nodes.push(node);
+		self.nodes.len() - 1
+	}
+
+	fn connect(&mut self, from: usize, to: usize) {
+		self.nodes[from].successors.push(to);
+		self.nodes[to].predecessors.push(from);
+	}
+
+	/// Adds a new node holding the statement and returns its index.
+	pub fn add_statement(&mut self, statement: Statement<T>) -> usize {
+		self.add_node(statement, self.nodes.len())
+	}
+
+	/// Joins all nodes without successors into a new no-operation node, if there
+	/// is more than one such node. The new exit node is returned, if applicable.
+	/// The [`ExitUnifier`] pass does the same for any view and set of nodes.
+	///
+	/// [`ExitUnifier`]: crate::pass::exit_unifier::ExitUnifier
+	pub fn set_single_exit(&mut self) -> Option<usize> {
+		let mut exits = self.nodes.iter().filter(|node| node.successors.is_empty());
+
+		if exits.next().is_some() && exits.next().is_some() {
+			let len = self.nodes.len();
+			let exit = self.add_statement(Statement::NoOperation);
+
+			for id in 0..len {
+				if self.nodes[id].successors.is_empty() {
+					self.connect(id, exit);
+				}
+			}
+
+			Some(exit)
+		} else {
+			None
+		}
+	}
+}
+
+impl<T: Clone> List<T> {
+	/// Sets whether nodes may be duplicated by cloning their payload, which
+	/// lets [`View::duplicate`] be used on the graph. The default is `false`.
+	pub fn set_duplicable(&mut self, duplicable: bool) {
+		self.copy = duplicable.then_some(T::clone);
+	}
+}
+
+impl<T> Default for List<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T> Predecessors for List<T> {
+	fn predecessors(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+		self.nodes[id].predecessors.iter().copied()
+	}
+}
+
+impl<T> Successors for List<T> {
+	fn successors(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+		self.nodes[id].successors.iter().copied()
+	}
+}
+
+impl<T> Classify for List<T> {
+	fn kind(&self, id: usize) -> Kind {
+		match self.nodes[id].statement {
+			Statement::NoOperation => Kind::NoOperation,
+			Statement::Simple(_) => Kind::Original,
+			Statement::Select { flag } => Kind::Selection(flag),
+			Statement::Assign { flag, value } => Kind::Assignment(flag, value),
+		}
+	}
+
+	fn origin(&self, id: usize) -> usize {
+		self.nodes[id].origin
+	}
+}
+
+impl<T> View for List<T> {
+	fn has_assignment(&self, id: usize, flag: Flag) -> bool {
+		matches!(self.nodes[id].statement, Statement::Assign { flag: other, .. } if other == flag)
+	}
+
+	fn add_no_operation(&mut self) -> usize {
+		self.add_statement(Statement::NoOperation)
+	}
+
+	fn add_selection(&mut self, flag: Flag) -> usize {
+		self.add_statement(Statement::Select { flag })
+	}
+
+	fn add_assignment(&mut self, flag: Flag, value: usize) -> usize {
+		self.add_statement(Statement::Assign { flag, value })
+	}
+
+	fn add_edge(&mut self, from: usize, to: usize) {
+		self.connect(from, to);
+	}
+
+	// The order of successors is meaningful to selections, so it is kept, while
+	// the order of predecessors is not and allows a cheaper removal.
+	fn replace_edge(&mut self, from: usize, to: usize, new: usize) {
+		let successor = self.nodes[from]
+			.successors
+			.iter()
+			.position(|&id| id == to)
+			.unwrap();
+
+		self.nodes[from].successors[successor] = new;
+		self.nodes[new].predecessors.push(from);
+
+		let predecessor = self.nodes[to]
+			.predecessors
+			.iter()
+			.position(|&id| id == from)
+			.unwrap();
+
+		self.nodes[to].predecessors.swap_remove(predecessor);
+	}
+
+	fn can_duplicate(&self, _id: usize) -> bool {
+		self.copy.is_some()
+	}
+
+	fn duplicate(&mut self, id: usize) -> usize {
+		let copy = self.copy.expect("the graph can not copy nodes");
+		let Node {
+			statement, origin, ..
+		} = &self.nodes[id];
+
+		let statement = match statement {
+			Statement::NoOperation => Statement::NoOperation,
+			Statement::Simple(payload) => Statement::Simple(copy(payload)),
+			&Statement::Select { flag } => Statement::Select { flag },
+			&Statement::Assign { flag, value } => Statement::Assign { flag, value },
+		};
+
+		self.add_node(statement, *origin)
+	}
+}
@@ -1,5 +1,36 @@
+use std::ops::ControlFlow;
+
 use crate::{set::Set, view::Successors};
 
+/// The kind of an edge found by a depth first search.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Edge {
+	/// An edge to a node not yet visited, which is visited through it.
+	Tree,
+
+	/// An edge to a node still being visited, which is an ancestor of the source.
+	Back,
+
+	/// An edge to a finished descendant of the source.
+	Forward,
+
+	/// An edge to a finished node that is not a descendant of the source.
+	Cross,
+}
+
+/// An event of a depth first search.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Event {
+	/// The node is visited, before any of its successors.
+	Pre(usize),
+
+	/// The node is finished, after all of its successors.
+	Post(usize),
+
+	/// The edge from the node being visited is followed.
+	Edge { kind: Edge, from: usize, to: usize },
+}
+
 struct Visit {
 	id: usize,
 	successors: std::ops::Range<usize>,
@@ -8,6 +39,10 @@ struct Visit {
 pub struct DepthFirstSearcher {
 	visits: Vec<Visit>,
 	nodes: Set,
+	pre: Vec<usize>,
+	post: Vec<usize>,
+	pre_order: Vec<usize>,
+	post_order: Vec<usize>,
 
 	successors: Vec<usize>,
 }
@@ -18,6 +53,10 @@ impl DepthFirstSearcher {
 		Self {
 			visits: Vec::new(),
 			nodes: Set::new(),
+			pre: Vec::new(),
+			post: Vec::new(),
+			pre_order: Vec::new(),
+			post_order: Vec::new(),
 
 			successors: Vec::new(),
 		}
@@ -54,6 +93,29 @@ impl DepthFirstSearcher {
 		&mut self.nodes
 	}
 
+	/// Returns the nodes finished by the classified runs since the numbers were last
+	/// cleared, in post-order.
+	#[must_use]
+	pub fn post_order(&self) -> &[usize] {
+		&self.post_order
+	}
+
+	/// Returns the nodes finished by the classified runs since the numbers were last
+	/// cleared, in reverse post-order, which starts with the start node of the latest one.
+	pub fn reverse_post_order(&self) -> impl Iterator<Item = usize> + '_ {
+		self.post_order.iter().rev().copied()
+	}
+
+	/// Returns the position of the node in the reverse post-order of the classified
+	/// runs since the numbers were last cleared, if it was finished by one of them.
+	#[must_use]
+	pub fn reverse_post_number(&self, id: usize) -> Option<usize> {
+		self.post
+			.get(id)
+			.filter(|&&number| number != usize::MAX)
+			.map(|number| self.post_order.len() - 1 - number)
+	}
+
 	pub fn run<N, H>(&mut self, view: &N, start: usize, mut handler: H)
 	where
 		N: Successors,
@@ -77,6 +139,137 @@ impl DepthFirstSearcher {
 			}
 		}
 	}
+
+	fn is_visited(&self, id: usize) -> bool {
+		self.pre.get(id).is_some_and(|&number| number != usize::MAX)
+	}
+
+	/// Forgets the nodes numbered by earlier classified runs, so that the next one
+	/// starts the orders anew. This takes time proportional to the nodes numbered.
+	pub fn clear_numbers(&mut self) {
+		for &id in &self.pre_order {
+			self.pre[id] = usize::MAX;
+			self.post[id] = usize::MAX;
+		}
+
+		self.pre_order.clear();
+		self.post_order.clear();
+	}
+
+	fn queue_classified<N: Successors>(&mut self, view: &N, id: usize) {
+		self.nodes.remove(id);
+
+		if self.pre.len() <= id {
+			self.pre.resize(id + 1, usize::MAX);
+			self.post.resize(id + 1, usize::MAX);
+		}
+
+		self.pre[id] = self.pre_order.len();
+		self.pre_order.push(id);
+
+		let start = self.successors.len();
+
+		for successor in view.successors(id) {
+			if self.nodes.contains(successor) || self.is_visited(successor) {
+				self.successors.push(successor);
+			}
+		}
+
+		self.visits.push(Visit {
+			id,
+			successors: start..self.successors.len(),
+		});
+	}
+
+	// Nodes are visited when they are still part of the set, and otherwise the edge
+	// leads to a node visited earlier in this run.
+	fn classify(&self, from: usize, to: usize) -> Edge {
+		if self.nodes.contains(to) {
+			Edge::Tree
+		} else if self.post[to] == usize::MAX {
+			Edge::Back
+		} else if self.pre[to] > self.pre[from] {
+			Edge::Forward
+		} else {
+			Edge::Cross
+		}
+	}
+
+	fn run_classified_search<N, H, B>(
+		&mut self,
+		view: &N,
+		start: usize,
+		handler: &mut H,
+	) -> ControlFlow<B>
+	where
+		N: Successors,
+		H: FnMut(Event) -> ControlFlow<B>,
+	{
+		self.queue_classified(view, start);
+
+		handler(Event::Pre(start))?;
+
+		while let Some(visit) = self.visits.last_mut() {
+			let from = visit.id;
+
+			if let Some(successor) = visit.successors.next_back() {
+				let to = self.successors[successor];
+				let kind = self.classify(from, to);
+
+				handler(Event::Edge { kind, from, to })?;
+
+				if kind == Edge::Tree {
+					self.queue_classified(view, to);
+
+					handler(Event::Pre(to))?;
+				}
+			} else {
+				let visit = self.visits.pop().unwrap();
+
+				self.successors.truncate(visit.successors.start);
+				self.post[from] = self.post_order.len();
+				self.post_order.push(from);
+
+				handler(Event::Post(from))?;
+			}
+		}
+
+		ControlFlow::Continue(())
+	}
+
+	/// Searches the nodes reachable from the start, reporting every node as it is visited
+	/// and finished and every edge between the nodes searched as it is followed. Nodes are
+	/// numbered after those of earlier runs until [`DepthFirstSearcher::clear_numbers`] is
+	/// called, so edges to nodes they finished are followed as crossing edges, while edges
+	/// to other nodes outside of the set are not. The search stops early if the handler
+	/// breaks, returning its value, and the nodes it had not finished are forgotten, so
+	/// later runs treat them as outside of the set.
+	pub fn run_classified<N, H, B>(
+		&mut self,
+		view: &N,
+		start: usize,
+		mut handler: H,
+	) -> ControlFlow<B>
+	where
+		N: Successors,
+		H: FnMut(Event) -> ControlFlow<B>,
+	{
+		if !self.nodes.contains(start) {
+			return ControlFlow::Continue(());
+		}
+
+		let result = self.run_classified_search(view, start, &mut handler);
+
+		if result.is_break() {
+			for visit in self.visits.drain(..) {
+				self.pre[visit.id] = usize::MAX;
+			}
+
+			self.successors.clear();
+		}
+
+		result
+	}
 }
 
 impl Default for DepthFirstSearcher {
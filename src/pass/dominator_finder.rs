@@ -0,0 +1,237 @@
+// Resources:
+// "A Simple, Fast Dominance Algorithm",
+//     by Keith D. Cooper, Timothy J. Harvey, and Ken Kennedy
+
+use std::ops::ControlFlow;
+
+use crate::{
+	set::Slice,
+	view::{Predecessors, Successors},
+};
+
+use super::depth_first_searcher::DepthFirstSearcher;
+
+/// This structure finds the immediate dominator of every node reachable from a start
+/// node within a set. Running it on an [`Inverted`] view from the exit node instead
+/// finds the immediate post-dominators.
+///
+/// The dominator tree is also numbered in pre-order, so that whether a node dominates
+/// another is answered in constant time and the nodes it dominates are a single slice.
+///
+/// [`Inverted`]: super::inverted::Inverted
+pub struct DominatorFinder {
+	dominators: Vec<usize>,
+	children: Vec<usize>,
+	siblings: Vec<usize>,
+	numbers: Vec<usize>,
+	ends: Vec<usize>,
+	tree: Vec<usize>,
+
+	depth_first_searcher: DepthFirstSearcher,
+}
+
+impl DominatorFinder {
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			dominators: Vec::new(),
+			children: Vec::new(),
+			siblings: Vec::new(),
+			numbers: Vec::new(),
+			ends: Vec::new(),
+			tree: Vec::new(),
+
+			depth_first_searcher: DepthFirstSearcher::new(),
+		}
+	}
+
+	/// Returns the nodes reached by the last run in reverse post-order,
+	/// which starts with the start node.
+	pub fn reverse_post_order(&self) -> impl Iterator<Item = usize> + '_ {
+		self.depth_first_searcher.reverse_post_order()
+	}
+
+	/// Returns the position of the node in the reverse post-order, if it was reached.
+	#[must_use]
+	pub fn reverse_post_number(&self, id: usize) -> Option<usize> {
+		self.depth_first_searcher.reverse_post_number(id)
+	}
+
+	/// Returns the immediate dominator of the node, if it was reached and is not the start.
+	#[must_use]
+	pub fn immediate(&self, id: usize) -> Option<usize> {
+		self.dominators
+			.get(id)
+			.copied()
+			.filter(|&dominator| dominator != usize::MAX && dominator != id)
+	}
+
+	/// Returns the position of the node in the pre-order of the dominator tree, if it was reached.
+	#[must_use]
+	pub fn tree_number(&self, id: usize) -> Option<usize> {
+		self.has_dominator(id).then(|| self.numbers[id])
+	}
+
+	/// Returns the nodes dominated by the node in pre-order of the dominator tree,
+	/// starting with the node itself, or nothing if it was not reached.
+	#[must_use]
+	pub fn subtree(&self, id: usize) -> &[usize] {
+		if self.has_dominator(id) {
+			&self.tree[self.numbers[id]..self.ends[id]]
+		} else {
+			&[]
+		}
+	}
+
+	/// Returns whether every path from the start to `id` goes through `dominator`.
+	/// Every reached node dominates itself.
+	#[must_use]
+	pub fn dominates(&self, dominator: usize, id: usize) -> bool {
+		self.has_dominator(dominator)
+			&& self.has_dominator(id)
+			&& (self.numbers[dominator]..self.ends[dominator]).contains(&self.numbers[id])
+	}
+
+	fn has_dominator(&self, id: usize) -> bool {
+		self.dominators.get(id).is_some_and(|&id| id != usize::MAX)
+	}
+
+	fn clear(&mut self) {
+		for id in self.depth_first_searcher.reverse_post_order() {
+			self.dominators[id] = usize::MAX;
+		}
+	}
+
+	fn find_reverse_post_order<N: Successors>(&mut self, view: &N, set: Slice, start: usize) {
+		self.depth_first_searcher.nodes_mut().clone_from_slice(set);
+		self.depth_first_searcher.clear_numbers();
+
+		let _ = self
+			.depth_first_searcher
+			.run_classified(view, start, |_| ControlFlow::<()>::Continue(()));
+
+		let len = self.reverse_post_order().max().map_or(0, |id| id + 1);
+
+		if self.dominators.len() < len {
+			self.dominators.resize(len, usize::MAX);
+			self.children.resize(len, usize::MAX);
+			self.siblings.resize(len, usize::MAX);
+			self.numbers.resize(len, 0);
+			self.ends.resize(len, 0);
+		}
+	}
+
+	fn position(&self, id: usize) -> usize {
+		self.reverse_post_number(id).unwrap()
+	}
+
+	fn find_intersection(&self, mut a: usize, mut b: usize) -> usize {
+		while a != b {
+			while self.position(a) > self.position(b) {
+				a = self.dominators[a];
+			}
+
+			while self.position(b) > self.position(a) {
+				b = self.dominators[b];
+			}
+		}
+
+		a
+	}
+
+	fn find_dominator<N: Predecessors>(&self, view: &N, id: usize) -> usize {
+		view.predecessors(id)
+			.filter(|&id| self.has_dominator(id))
+			.reduce(|a, b| self.find_intersection(a, b))
+			.unwrap_or(usize::MAX)
+	}
+
+	fn find_children(&mut self, start: usize) {
+		for id in self.depth_first_searcher.reverse_post_order() {
+			self.children[id] = usize::MAX;
+		}
+
+		// Children are linked in reverse post-order, so they are prepended in post-order.
+		for &id in self.depth_first_searcher.post_order() {
+			if id != start {
+				let dominator = self.dominators[id];
+
+				self.siblings[id] = self.children[dominator];
+				self.children[dominator] = id;
+			}
+		}
+	}
+
+	fn find_tree(&mut self, start: usize) {
+		self.tree.clear();
+
+		let mut id = start;
+
+		loop {
+			self.numbers[id] = self.tree.len();
+			self.tree.push(id);
+
+			if self.children[id] != usize::MAX {
+				id = self.children[id];
+
+				continue;
+			}
+
+			loop {
+				self.ends[id] = self.tree.len();
+
+				if id == start {
+					return;
+				}
+
+				if self.siblings[id] == usize::MAX {
+					id = self.dominators[id];
+				} else {
+					id = self.siblings[id];
+
+					break;
+				}
+			}
+		}
+	}
+
+	/// Finds the dominators of the nodes in the set reachable from the start node.
+	pub fn run<N>(&mut self, view: &N, set: Slice, start: usize)
+	where
+		N: Predecessors + Successors,
+	{
+		self.clear();
+		self.find_reverse_post_order(view, set, start);
+
+		let Some((&first, rest)) = self.depth_first_searcher.post_order().split_last() else {
+			return;
+		};
+
+		self.dominators[first] = first;
+
+		let mut changed = true;
+
+		while changed {
+			changed = false;
+
+			for &id in rest.iter().rev() {
+				let dominator = self.find_dominator(view, id);
+
+				if self.dominators[id] != dominator {
+					self.dominators[id] = dominator;
+
+					changed = true;
+				}
+			}
+		}
+
+		self.find_children(first);
+		self.find_tree(first);
+	}
+}
+
+impl Default for DominatorFinder {
+	fn default() -> Self {
+		Self::new()
+	}
+}
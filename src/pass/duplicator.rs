@@ -0,0 +1,84 @@
+use crate::view::View;
+
+/// This structure copies a region of nodes along with the edges between them.
+/// Edges leaving the region are kept going to the original nodes.
+pub struct Duplicator {
+	copies: Vec<usize>,
+	region: Vec<usize>,
+
+	successors: Vec<usize>,
+}
+
+impl Duplicator {
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			copies: Vec::new(),
+			region: Vec::new(),
+
+			successors: Vec::new(),
+		}
+	}
+
+	/// Returns the copy of the node made by the last run, if any.
+	#[must_use]
+	pub fn copy_of(&self, id: usize) -> Option<usize> {
+		self.copies
+			.get(id)
+			.copied()
+			.filter(|&copy| copy != usize::MAX)
+	}
+
+	/// Returns the copies made by the last run.
+	pub fn copies(&self) -> impl Iterator<Item = usize> + '_ {
+		self.region.iter().filter_map(|&id| self.copy_of(id))
+	}
+
+	fn clear(&mut self) {
+		for &id in &self.region {
+			self.copies[id] = usize::MAX;
+		}
+
+		self.region.clear();
+	}
+
+	/// Returns whether the view can copy every node of the given region.
+	#[must_use]
+	pub fn can_run<N: View>(view: &N, region: &[usize]) -> bool {
+		region.iter().all(|&id| view.can_duplicate(id))
+	}
+
+	/// Copies the given region of nodes, which must not repeat. The view must be able
+	/// to copy every node of it, which can be checked with [`Duplicator::can_run`].
+	pub fn run<N: View>(&mut self, view: &mut N, region: &[usize]) {
+		self.clear();
+
+		for &id in region {
+			let copy = view.duplicate(id);
+
+			if self.copies.len() <= id {
+				self.copies.resize(id + 1, usize::MAX);
+			}
+
+			self.copies[id] = copy;
+			self.region.push(id);
+		}
+
+		for &id in region {
+			self.successors.clear();
+			self.successors.extend(view.successors(id));
+
+			for &successor in &self.successors {
+				let copy = self.copy_of(successor).unwrap_or(successor);
+
+				view.add_edge(self.copies[id], copy);
+			}
+		}
+	}
+}
+
+impl Default for Duplicator {
+	fn default() -> Self {
+		Self::new()
+	}
+}
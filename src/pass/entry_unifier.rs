@@ -0,0 +1,104 @@
+use crate::{
+	set::Set,
+	view::{Flag, Predecessors, View},
+};
+
+/// This structure joins the nodes of a set without predecessors in it, along with any other
+/// entry points given, into a single start. When there is more than one, a selection
+/// on [`Flag::C`] is added as the start and each entry gets an assignment leading to it.
+/// These assignments are where the graph is entered and are not added to the set, as the
+/// selection reads the flag that only they assign.
+pub struct EntryUnifier {
+	entries: Vec<usize>,
+	stubs: Vec<usize>,
+}
+
+impl EntryUnifier {
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			entries: Vec::new(),
+			stubs: Vec::new(),
+		}
+	}
+
+	/// Returns the entries found by the last run, in ascending order.
+	#[must_use]
+	pub fn entries(&self) -> &[usize] {
+		&self.entries
+	}
+
+	/// Returns the assignments added by the last run, in the same order as the entries
+	/// they stand for. Entering the graph at one of them behaves as entering at its entry.
+	#[must_use]
+	pub fn stubs(&self) -> &[usize] {
+		&self.stubs
+	}
+
+	fn find_entries<N: Predecessors>(&mut self, view: &N, set: &Set, entries: &[usize]) {
+		self.entries.clear();
+		self.entries.extend(
+			set.ascending()
+				.filter(|&id| !view.predecessors(id).any(|id| set.contains(id))),
+		);
+
+		self.entries
+			.extend(entries.iter().copied().filter(|&id| set.contains(id)));
+
+		self.entries.sort_unstable();
+		self.entries.dedup();
+	}
+
+	fn set_new_start<N: View>(&mut self, view: &mut N, set: &mut Set) -> usize {
+		let start = view.add_selection(Flag::C);
+
+		set.grow_insert(start);
+
+		for (index, &entry) in self.entries.iter().enumerate() {
+			let stub = view.add_assignment(Flag::C, index);
+
+			view.add_edge(stub, start);
+			view.add_edge(start, entry);
+
+			self.stubs.push(stub);
+		}
+
+		start
+	}
+
+	/// Joins the nodes without predecessors in the set and the given entries of it,
+	/// adding the new start to it. The start is returned, if there is one. When it is
+	/// a new selection, the graph must be entered through one of the [`stubs`] instead.
+	///
+	/// [`stubs`]: EntryUnifier::stubs
+	pub fn run_with_entries<N: View>(
+		&mut self,
+		view: &mut N,
+		set: &mut Set,
+		entries: &[usize],
+	) -> Option<usize> {
+		self.find_entries(view, set, entries);
+		self.stubs.clear();
+
+		match *self.entries.as_slice() {
+			[] => None,
+			[start] => Some(start),
+			_ => Some(self.set_new_start(view, set)),
+		}
+	}
+
+	/// Joins the nodes without predecessors in the set, adding the new start to it.
+	/// The start is returned, if there is one. When it is a new selection, the graph
+	/// must be entered through one of the [`stubs`] instead.
+	///
+	/// [`stubs`]: EntryUnifier::stubs
+	pub fn run<N: View>(&mut self, view: &mut N, set: &mut Set) -> Option<usize> {
+		self.run_with_entries(view, set, &[])
+	}
+}
+
+impl Default for EntryUnifier {
+	fn default() -> Self {
+		Self::new()
+	}
+}
@@ -0,0 +1,99 @@
+use crate::{
+	set::Set,
+	view::{Flag, Successors, View},
+};
+
+/// This structure joins the nodes of a set without successors in it into a single exit.
+/// A no-operation node is only added when there is more than one such node.
+pub struct ExitUnifier {
+	exits: Vec<usize>,
+	kinds: Vec<usize>,
+}
+
+impl ExitUnifier {
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			exits: Vec::new(),
+			kinds: Vec::new(),
+		}
+	}
+
+	/// Returns the exits found by the last run, in ascending order.
+	#[must_use]
+	pub fn exits(&self) -> &[usize] {
+		&self.exits
+	}
+
+	fn find_exits<N: Successors>(&mut self, view: &N, set: &Set) {
+		self.exits.clear();
+		self.exits.extend(
+			set.ascending()
+				.filter(|&id| !view.successors(id).any(|id| set.contains(id))),
+		);
+	}
+
+	fn set_new_exit<N: View>(&mut self, view: &mut N, set: &mut Set, flag: Flag) -> usize {
+		let exit = view.add_no_operation();
+		let is_mixed = self.kinds.iter().any(|&kind| kind != self.kinds[0]);
+
+		set.grow_insert(exit);
+
+		for (&id, &kind) in self.exits.iter().zip(&self.kinds) {
+			if is_mixed {
+				let assignment = view.add_assignment(flag, kind);
+
+				view.add_edge(id, assignment);
+				view.add_edge(assignment, exit);
+
+				set.grow_insert(assignment);
+			} else {
+				view.add_edge(id, exit);
+			}
+		}
+
+		exit
+	}
+
+	/// Joins the exits of the set, adding any new nodes to it. Each exit is given a kind,
+	/// such as whether it returns or traps, and when they differ the kind is assigned to
+	/// the flag on the way to the new exit. As [`Branch`] assigns [`Flag::A`] on its way
+	/// to the exit, the flag should be another if it runs afterwards.
+	/// The exit is returned, if there is one.
+	///
+	/// [`Branch`]: crate::branch::Branch
+	pub fn run_with_kinds<N, K>(
+		&mut self,
+		view: &mut N,
+		set: &mut Set,
+		flag: Flag,
+		kind: K,
+	) -> Option<usize>
+	where
+		N: View,
+		K: FnMut(usize) -> usize,
+	{
+		self.find_exits(view, set);
+
+		self.kinds.clear();
+		self.kinds.extend(self.exits.iter().copied().map(kind));
+
+		match *self.exits.as_slice() {
+			[] => None,
+			[exit] => Some(exit),
+			_ => Some(self.set_new_exit(view, set, flag)),
+		}
+	}
+
+	/// Joins the exits of the set, adding any new nodes to it.
+	/// The exit is returned, if there is one.
+	pub fn run<N: View>(&mut self, view: &mut N, set: &mut Set) -> Option<usize> {
+		self.run_with_kinds(view, set, Flag::C, |_| 0)
+	}
+}
+
+impl Default for ExitUnifier {
+	fn default() -> Self {
+		Self::new()
+	}
+}
@@ -0,0 +1,242 @@
+// Resources:
+// "On Loops, Dominators, and Dominance Frontiers",
+//     by G. Ramalingam
+// "Identifying Loops Using DJ Graphs",
+//     by Vugranam C. Sreedhar, Guang R. Gao, and Yong-Fong Lee
+
+use crate::{
+	set::{Set, Slice},
+	view::{Predecessors, Successors},
+};
+
+use super::strongly_connected_finder::{StronglyConnected, StronglyConnectedFinder};
+
+/// A loop of the forest, with the nodes of its strongly connected region, the nodes
+/// entered from outside of it in ascending order, and the loops nested in it.
+#[derive(Clone)]
+pub struct Nest {
+	pub nodes: Set,
+	pub entries: Vec<usize>,
+	pub parent: Option<usize>,
+	pub children: Vec<usize>,
+}
+
+/// This structure finds the loop nesting forest of a set. Every entry of a loop acts as
+/// its header, so the loops nested in it are the strongly connected regions left after
+/// removing all of its entries, as is done once [`Repeat`] gives it a single start.
+/// The start is an entry of any region it is in, even without predecessors, and
+/// regions that are never entered have no loops nested in them.
+///
+/// Each level of the forest is found with a single search of the strongly connected
+/// regions, so the time taken is proportional to the number of nodes times the depth
+/// of the forest.
+///
+/// [`Repeat`]: crate::repeat::Repeat
+pub struct LoopNestingFinder<F = StronglyConnectedFinder> {
+	nests: Vec<Nest>,
+	roots: Vec<usize>,
+	parents: Vec<Option<usize>>,
+	pool: Vec<Set>,
+	start: Option<usize>,
+
+	strongly_connected_finder: F,
+}
+
+impl LoopNestingFinder {
+	#[must_use]
+	pub const fn new() -> Self {
+		Self::with_finder(StronglyConnectedFinder::new())
+	}
+}
+
+impl<F> LoopNestingFinder<F> {
+	/// Creates a new instance of the finder using the given finder of strongly
+	/// connected components.
+	#[must_use]
+	pub const fn with_finder(finder: F) -> Self {
+		Self {
+			nests: Vec::new(),
+			roots: Vec::new(),
+			parents: Vec::new(),
+			pool: Vec::new(),
+			start: None,
+
+			strongly_connected_finder: finder,
+		}
+	}
+
+	/// Returns the loops found, with parents before their children. Loops replaced
+	/// by [`LoopNestingFinder::set_nested`] are kept but are no longer reachable.
+	#[must_use]
+	pub fn nests(&self) -> &[Nest] {
+		&self.nests
+	}
+
+	/// Returns the loops not nested in any other, in the order they were found.
+	#[must_use]
+	pub fn roots(&self) -> &[usize] {
+		&self.roots
+	}
+
+	/// Returns the loops nested directly in the given one, or the roots if there is none.
+	#[must_use]
+	pub fn children(&self, parent: Option<usize>) -> &[usize] {
+		parent.map_or(&self.roots, |parent| &self.nests[parent].children)
+	}
+}
+
+impl<F> LoopNestingFinder<F> {
+	fn is_repetition<N: Successors>(view: &N, list: &[usize]) -> bool {
+		if let &[first] = list {
+			view.successors(first).any(|id| id == first)
+		} else {
+			!list.is_empty()
+		}
+	}
+
+	fn find_entries<N: Predecessors>(view: &N, nodes: &Set, start: Option<usize>) -> Vec<usize> {
+		nodes
+			.ascending()
+			.filter(|&id| start == Some(id) || view.predecessors(id).any(|id| !nodes.contains(id)))
+			.collect()
+	}
+
+	fn set_parent(parents: &mut Vec<Option<usize>>, id: usize, parent: Option<usize>) {
+		if id >= parents.len() {
+			parents.resize(id + 1, None);
+		}
+
+		parents[id] = parent;
+	}
+
+	// Regions never cross the loops they are nested in, so the loop of each one is
+	// that of any of its nodes.
+	fn add_regions<N>(&mut self, view: &N, set: Slice)
+	where
+		N: Predecessors + Successors,
+		F: StronglyConnected<N>,
+	{
+		self.strongly_connected_finder.run(view, set, |list| {
+			if !Self::is_repetition(view, list) {
+				return;
+			}
+
+			let mut nodes = self.pool.pop().unwrap_or_default();
+
+			nodes.clear();
+			nodes.extend(list.iter().copied());
+
+			let index = self.nests.len();
+			let parent = self.parents[list[0]];
+
+			match parent {
+				Some(parent) => self.nests[parent].children.push(index),
+				None => self.roots.push(index),
+			}
+
+			self.nests.push(Nest {
+				entries: Self::find_entries(view, &nodes, self.start),
+				nodes,
+				parent,
+				children: Vec::new(),
+			});
+		});
+	}
+
+	// Loops are added level by level, with all of the loops nested in those of
+	// a level found together by a single search.
+	fn add_nested_from<N>(&mut self, view: &N, mut first: usize)
+	where
+		N: Predecessors + Successors,
+		F: StronglyConnected<N>,
+	{
+		let mut child = self.pool.pop().unwrap_or_default();
+
+		while first < self.nests.len() {
+			let last = self.nests.len();
+			let mut is_empty = true;
+
+			for index in first..last {
+				let nest = &self.nests[index];
+
+				if nest.entries.is_empty() {
+					continue;
+				}
+
+				for id in nest.nodes.ascending() {
+					Self::set_parent(&mut self.parents, id, Some(index));
+				}
+
+				if is_empty {
+					child.clone_from(&nest.nodes);
+				} else {
+					child.extend(nest.nodes.ascending());
+				}
+
+				for &entry in &nest.entries {
+					child.remove(entry);
+				}
+
+				is_empty = false;
+			}
+
+			if is_empty {
+				break;
+			}
+
+			self.add_regions(view, child.as_slice());
+
+			first = last;
+		}
+
+		self.pool.push(child);
+	}
+
+	/// Adds the loops found in the set as nested in the given loop, or as roots if there
+	/// is none, along with everything nested in them.
+	pub fn add_nested<N>(&mut self, view: &N, parent: Option<usize>, set: Slice)
+	where
+		N: Predecessors + Successors,
+		F: StronglyConnected<N>,
+	{
+		let first = self.nests.len();
+
+		for id in set {
+			Self::set_parent(&mut self.parents, id, parent);
+		}
+
+		self.add_regions(view, set);
+		self.add_nested_from(view, first);
+	}
+
+	/// Replaces the loops nested in the given loop by those found in the set, such as when
+	/// the loop was restructured differently than its entries suggested.
+	pub fn set_nested<N>(&mut self, view: &N, parent: usize, set: Slice)
+	where
+		N: Predecessors + Successors,
+		F: StronglyConnected<N>,
+	{
+		self.nests[parent].children.clear();
+		self.add_nested(view, Some(parent), set);
+	}
+
+	/// Finds the loop nesting forest of the set, entered at the start if there is one.
+	pub fn run<N>(&mut self, view: &N, set: Slice, start: Option<usize>)
+	where
+		N: Predecessors + Successors,
+		F: StronglyConnected<N>,
+	{
+		self.pool
+			.extend(self.nests.drain(..).map(|nest| nest.nodes));
+
+		self.roots.clear();
+		self.start = start;
+		self.add_nested(view, None, set);
+	}
+}
+
+impl Default for LoopNestingFinder {
+	fn default() -> Self {
+		Self::new()
+	}
+}
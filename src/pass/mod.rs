@@ -1,3 +1,10 @@
 pub mod depth_first_searcher;
+pub mod dominator_finder;
+pub mod duplicator;
+pub mod entry_unifier;
+pub mod exit_unifier;
 pub mod inverted;
+pub mod loop_nesting_finder;
+pub mod pearce_finder;
+pub mod reducibility_finder;
 pub mod strongly_connected_finder;
@@ -0,0 +1,165 @@
+// Resources:
+// "A Space-Efficient Algorithm for Finding Strongly Connected Components",
+//     by David J. Pearce
+
+use crate::{set::Slice, view::Successors};
+
+use super::strongly_connected_finder::StronglyConnected;
+
+struct Visit {
+	id: usize,
+	successors: std::ops::Range<usize>,
+	root: bool,
+}
+
+/// This structure finds the strongly connected components of a set in a single
+/// search, using only the successors of each node. Components are found with
+/// those they lead to first.
+pub struct PearceFinder {
+	indices: Vec<usize>,
+	stack: Vec<usize>,
+	found: Vec<usize>,
+	index: usize,
+	component: usize,
+
+	visits: Vec<Visit>,
+	successors: Vec<usize>,
+}
+
+impl PearceFinder {
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			indices: Vec::new(),
+			stack: Vec::new(),
+			found: Vec::new(),
+			index: 0,
+			component: 0,
+
+			visits: Vec::new(),
+			successors: Vec::new(),
+		}
+	}
+
+	fn queue_visit<N: Successors>(&mut self, view: &N, set: Slice, id: usize) {
+		let start = self.successors.len();
+
+		self.indices[id] = self.index;
+		self.index += 1;
+
+		self.successors
+			.extend(view.successors(id).filter(|&id| set.contains(id)));
+
+		self.visits.push(Visit {
+			id,
+			successors: start..self.successors.len(),
+			root: true,
+		});
+	}
+
+	// Nodes finished without being a root have their index lowered to that of a node
+	// still being visited, and components are given indices from the top down so
+	// that they are never lower than those of nodes being visited.
+	fn set_lowest(&mut self, id: usize, successor: usize) {
+		if self.indices[successor] < self.indices[id] {
+			self.indices[id] = self.indices[successor];
+
+			if let Some(visit) = self.visits.last_mut() {
+				visit.root = false;
+			}
+		}
+	}
+
+	fn set_finished<H: FnMut(&[usize])>(&mut self, visit: &Visit, handler: &mut H) {
+		if !visit.root {
+			self.stack.push(visit.id);
+
+			return;
+		}
+
+		self.found.clear();
+		self.found.push(visit.id);
+		self.index -= 1;
+
+		while let Some(&id) = self.stack.last() {
+			if self.indices[visit.id] > self.indices[id] {
+				break;
+			}
+
+			self.stack.pop();
+			self.found.push(id);
+			self.indices[id] = self.component;
+			self.index -= 1;
+		}
+
+		self.indices[visit.id] = self.component;
+		self.component -= 1;
+
+		handler(&self.found);
+	}
+
+	fn run_search<N, H>(&mut self, view: &N, set: Slice, start: usize, handler: &mut H)
+	where
+		N: Successors,
+		H: FnMut(&[usize]),
+	{
+		self.queue_visit(view, set, start);
+
+		while let Some(visit) = self.visits.last_mut() {
+			let id = visit.id;
+
+			if let Some(position) = visit.successors.next() {
+				let successor = self.successors[position];
+
+				if self.indices[successor] == 0 {
+					self.queue_visit(view, set, successor);
+				} else {
+					self.set_lowest(id, successor);
+				}
+			} else {
+				let visit = self.visits.pop().unwrap();
+
+				self.successors.truncate(visit.successors.start);
+				self.set_finished(&visit, handler);
+
+				if let Some(parent) = self.visits.last() {
+					self.set_lowest(parent.id, id);
+				}
+			}
+		}
+	}
+
+	pub fn run<N, H>(&mut self, view: &N, set: Slice, mut handler: H)
+	where
+		N: Successors,
+		H: FnMut(&[usize]),
+	{
+		let len = set.into_iter().max().map_or(0, |id| id + 1);
+
+		self.indices.clear();
+		self.indices.resize(len, 0);
+		self.index = 1;
+		self.component = usize::MAX;
+
+		for id in set {
+			if self.indices[id] == 0 {
+				self.run_search(view, set, id, &mut handler);
+			}
+		}
+	}
+}
+
+impl<N: Successors> StronglyConnected<N> for PearceFinder {
+	fn run<H>(&mut self, view: &N, set: Slice, handler: H)
+	where
+		H: FnMut(&[usize]),
+	{
+		Self::run(self, view, set, handler);
+	}
+}
+
+impl Default for PearceFinder {
+	fn default() -> Self {
+		Self::new()
+	}
+}
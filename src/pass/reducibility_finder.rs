@@ -0,0 +1,149 @@
+use crate::{
+	set::{Set, Slice},
+	view::{Predecessors, Successors},
+};
+
+use super::{depth_first_searcher::DepthFirstSearcher, loop_nesting_finder::LoopNestingFinder};
+
+/// A node entering a region, with its predecessors from outside of the region.
+/// The start node is an entry of any region it is in, even without predecessors.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Entry {
+	pub id: usize,
+	pub predecessors: Vec<usize>,
+}
+
+/// A strongly connected region, which is a natural loop if it has a single entry
+/// dominating all of its nodes, and is irreducible otherwise. Regions that cannot
+/// be reached from the start are neither, as no node of theirs has dominators.
+#[derive(Clone)]
+pub struct Region {
+	pub nodes: Set,
+	pub entries: Vec<Entry>,
+	pub parent: Option<usize>,
+	pub reachable: bool,
+	pub irreducible: bool,
+}
+
+/// This structure classifies the loops of the nesting forest found by [`LoopNestingFinder`],
+/// which matches how [`Repeat`] finds repetitions, as either natural loops or irreducible
+/// regions. Every path from the start into a region goes through one of its entries that
+/// is the start or has a predecessor reached from it, so a reachable region is a natural
+/// loop exactly when it has a single such entry, which then dominates it.
+///
+/// [`Repeat`]: crate::repeat::Repeat
+pub struct ReducibilityFinder {
+	regions: Vec<Region>,
+	reachable: Set,
+
+	loop_nesting_finder: LoopNestingFinder,
+	depth_first_searcher: DepthFirstSearcher,
+}
+
+impl ReducibilityFinder {
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			regions: Vec::new(),
+			reachable: Set::new(),
+
+			loop_nesting_finder: LoopNestingFinder::new(),
+			depth_first_searcher: DepthFirstSearcher::new(),
+		}
+	}
+
+	/// Returns the regions found by the last run, with parents before their children.
+	#[must_use]
+	pub fn regions(&self) -> &[Region] {
+		&self.regions
+	}
+
+	/// Returns the irreducible regions found by the last run. These are given a single
+	/// start by [`Repeat`], with a selection on [`Flag::C`] unless it can duplicate nodes
+	/// instead, which it also does for regions only entered many times from nodes that
+	/// cannot be reached.
+	///
+	/// [`Flag::C`]: crate::view::Flag::C
+	/// [`Repeat`]: crate::repeat::Repeat
+	pub fn irreducible(&self) -> impl Iterator<Item = &Region> + '_ {
+		self.regions.iter().filter(|region| region.irreducible)
+	}
+
+	/// Returns whether the last run found no irreducible regions.
+	#[must_use]
+	pub fn is_reducible(&self) -> bool {
+		self.irreducible().next().is_none()
+	}
+
+	fn find_reachable<N: Successors>(&mut self, view: &N, set: Slice, start: usize) {
+		self.reachable.clear();
+
+		self.depth_first_searcher.nodes_mut().clone_from_slice(set);
+		self.depth_first_searcher.run(view, start, |id, post| {
+			if !post {
+				self.reachable.grow_insert(id);
+			}
+		});
+	}
+
+	fn is_entered(&self, entry: &Entry, start: usize) -> bool {
+		entry.id == start
+			|| entry
+				.predecessors
+				.iter()
+				.any(|&id| self.reachable.contains(id))
+	}
+
+	fn find_entries<N: Predecessors>(view: &N, nodes: &Set, entries: &[usize]) -> Vec<Entry> {
+		entries
+			.iter()
+			.map(|&id| Entry {
+				id,
+				predecessors: view
+					.predecessors(id)
+					.filter(|&id| !nodes.contains(id))
+					.collect(),
+			})
+			.collect()
+	}
+
+	/// Finds and classifies the regions of the set entered at the start.
+	pub fn run<N>(&mut self, view: &N, set: Slice, start: usize)
+	where
+		N: Predecessors + Successors,
+	{
+		self.regions.clear();
+
+		self.find_reachable(view, set, start);
+		self.loop_nesting_finder.run(view, set, Some(start));
+
+		// Regions are either wholly reachable or not at all.
+		for nest in self.loop_nesting_finder.nests() {
+			let reachable = nest
+				.nodes
+				.ascending()
+				.next()
+				.is_some_and(|id| self.reachable.contains(id));
+
+			let entries = Self::find_entries(view, &nest.nodes, &nest.entries);
+			let entered = entries
+				.iter()
+				.filter(|entry| self.is_entered(entry, start))
+				.count();
+
+			self.regions.push(Region {
+				nodes: nest.nodes.clone(),
+				entries,
+				parent: nest.parent,
+				reachable,
+				irreducible: reachable && entered > 1,
+			});
+		}
+	}
+}
+
+impl Default for ReducibilityFinder {
+	fn default() -> Self {
+		Self::new()
+	}
+}
@@ -9,6 +9,14 @@ use crate::{
 
 use super::{depth_first_searcher::DepthFirstSearcher, inverted::Inverted};
 
+/// A pass that finds the strongly connected components of a set of nodes,
+/// calling the handler with the nodes of each one.
+pub trait StronglyConnected<N: Successors> {
+	fn run<H>(&mut self, view: &N, set: Slice, handler: H)
+	where
+		H: FnMut(&[usize]);
+}
+
 pub struct StronglyConnectedFinder {
 	found: Vec<usize>,
 	post: Vec<usize>,
@@ -74,6 +82,15 @@ impl StronglyConnectedFinder {
 	}
 }
 
+impl<N: Predecessors + Successors> StronglyConnected<N> for StronglyConnectedFinder {
+	fn run<H>(&mut self, view: &N, set: Slice, handler: H)
+	where
+		H: FnMut(&[usize]),
+	{
+		Self::run(self, view, set, handler);
+	}
+}
+
 impl Default for StronglyConnectedFinder {
 	fn default() -> Self {
 		Self::new()
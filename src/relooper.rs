@@ -0,0 +1,352 @@
+use crate::{
+	list::{List, Statement},
+	pass::depth_first_searcher::DepthFirstSearcher,
+	set::Set,
+	structurer::Structurer,
+	tree::{Builder, Node},
+	view::{Flag, Successors, View},
+};
+
+/// The condition under which an arm of a selection is taken.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Condition<'a, C> {
+	/// The condition of a branch added to a block, or none if it is taken by default.
+	Branch(Option<&'a C>),
+
+	/// The flag holds the given value.
+	Flag(Flag, usize),
+}
+
+/// A description of the first node found that could not be rendered.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RenderError {
+	/// A node added by restructuring that selects nothing has many successors.
+	Unselected { head: usize },
+
+	/// The latch of a loop leads somewhere other than the start or the end of a loop
+	/// around it.
+	Latch { latch: usize, successor: usize },
+}
+
+impl std::fmt::Display for RenderError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match *self {
+			Self::Unselected { head } => write!(f, "node {head} has many successors"),
+			Self::Latch { latch, successor } => {
+				write!(f, "latch {latch} leads to {successor} outside of its loops")
+			}
+		}
+	}
+}
+
+impl std::error::Error for RenderError {}
+
+/// A callback for rendering the structured code, in the order it is written.
+/// Selections hold arms, whose conditions are checked in order, and loops are
+/// repeated until a break.
+pub trait Render<B, C> {
+	/// Renders the code of a block.
+	fn block(&mut self, code: &B);
+
+	/// Renders an assignment of the value to the flag.
+	fn assignment(&mut self, flag: Flag, value: usize);
+
+	/// Renders the start of a selection, whose arms follow.
+	fn enter_selection(&mut self);
+
+	/// Renders the end of a selection, after its last arm.
+	fn leave_selection(&mut self);
+
+	/// Renders the start of an arm, taken when the condition holds.
+	fn enter_arm(&mut self, condition: Condition<'_, C>);
+
+	/// Renders the end of an arm, which continues after the selection.
+	fn leave_arm(&mut self);
+
+	/// Renders the start of a loop, whose body follows.
+	fn enter_loop(&mut self);
+
+	/// Renders the end of a loop, after its body.
+	fn leave_loop(&mut self);
+
+	/// Renders a jump to the end of the loop at the given depth, counting outwards
+	/// from `0` for the innermost loop.
+	fn break_loop(&mut self, depth: usize);
+
+	/// Renders a jump to the start of the loop at the given depth, counting outwards
+	/// from `0` for the innermost loop.
+	fn continue_loop(&mut self, depth: usize);
+}
+
+struct Block<B, C> {
+	code: B,
+	branches: Vec<(usize, Option<C>)>,
+}
+
+struct Blocks<'a, B, C>(&'a [Block<B, C>]);
+
+impl<B, C> Successors for Blocks<'_, B, C> {
+	fn successors(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+		self.0[id].branches.iter().map(|branch| branch.0)
+	}
+}
+
+/// This structure offers an interface similar to the Relooper of Emscripten. Blocks
+/// of code are added with branches between them, which are taken when their condition
+/// holds, or by default when they have none. The graph is then restructured and
+/// rendered through a [`Render`] callback.
+pub struct Relooper<B, C> {
+	blocks: Vec<Block<B, C>>,
+	list: List<usize>,
+	set: Set,
+	loops: Vec<(usize, usize, Option<usize>)>,
+
+	structurer: Structurer,
+	builder: Builder,
+	depth_first_searcher: DepthFirstSearcher,
+}
+
+impl<B, C> Relooper<B, C> {
+	/// Creates a new instance of the relooper.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			blocks: Vec::new(),
+			list: List::new(),
+			set: Set::new(),
+			loops: Vec::new(),
+
+			structurer: Structurer::new(),
+			builder: Builder::new(),
+			depth_first_searcher: DepthFirstSearcher::new(),
+		}
+	}
+
+	/// Adds a new block holding the code and returns its index.
+	pub fn add_block(&mut self, code: B) -> usize {
+		self.blocks.push(Block {
+			code,
+			branches: Vec::new(),
+		});
+
+		self.blocks.len() - 1
+	}
+
+	/// Adds a branch from the `from` block to the `to` block. Branches with a condition
+	/// are checked in the order they were added, and the branch without one is taken
+	/// when none of them hold. There should be only one branch without a condition.
+	pub fn add_branch(&mut self, from: usize, to: usize, condition: Option<C>) {
+		self.blocks[from].branches.push((to, condition));
+	}
+
+	// Branches with a condition come first, so they are checked before the default.
+	fn ordered(branches: &[(usize, Option<C>)]) -> impl Iterator<Item = &(usize, Option<C>)> {
+		let conditional = branches.iter().filter(|branch| branch.1.is_some());
+		let default = branches.iter().filter(|branch| branch.1.is_none());
+
+		conditional.chain(default)
+	}
+
+	fn find_reachable(&mut self, entry: usize) {
+		let nodes = self.depth_first_searcher.nodes_mut();
+
+		nodes.clear();
+		nodes.extend(0..self.blocks.len());
+
+		self.set.clear();
+		self.depth_first_searcher
+			.run(&Blocks(&self.blocks), entry, |id, post| {
+				if post {
+					self.set.grow_insert(id);
+				}
+			});
+	}
+
+	// Only reachable blocks have their branches added, as restructuring expects no
+	// edges from outside of the set. The entry is reached from a new node, so that
+	// it is entered from outside of any repetition it may be part of.
+	fn set_list(&mut self, entry: usize) -> usize {
+		self.list = List::with_capacity(self.blocks.len() + 1);
+
+		for block in 0..self.blocks.len() {
+			self.list.add_statement(Statement::Simple(block));
+		}
+
+		for block in self.set.ascending() {
+			for &(to, _) in Self::ordered(&self.blocks[block].branches) {
+				self.list.add_edge(block, to);
+			}
+		}
+
+		let start = self.list.add_no_operation();
+
+		self.list.add_edge(start, entry);
+		self.set.grow_insert(start);
+
+		start
+	}
+
+	// Successors are matched with branches by their position, so that many branches
+	// to the same block each keep their own condition.
+	fn find_condition(&self, head: usize, index: usize) -> Result<Condition<'_, C>, RenderError> {
+		match *self.list.statement(head) {
+			Statement::Simple(block) => {
+				let branches = &self.blocks[block].branches;
+				let branch = Self::ordered(branches)
+					.nth(index)
+					.ok_or(RenderError::Unselected { head })?;
+
+				Ok(Condition::Branch(branch.1.as_ref()))
+			}
+			Statement::Select { flag } => Ok(Condition::Flag(flag, index)),
+			Statement::NoOperation | Statement::Assign { .. } => {
+				Err(RenderError::Unselected { head })
+			}
+		}
+	}
+
+	fn render_statement<R: Render<B, C>>(&self, id: usize, renderer: &mut R) {
+		match *self.list.statement(id) {
+			Statement::Simple(block) => renderer.block(&self.blocks[block].code),
+			Statement::Assign { flag, value } => renderer.assignment(flag, value),
+			Statement::NoOperation | Statement::Select { .. } => {}
+		}
+	}
+
+	// Each successor of a latch is the start of a loop around it, which is continued,
+	// or the end of one, which is broken out of.
+	fn render_jump<R: Render<B, C>>(
+		&self,
+		latch: usize,
+		successor: usize,
+		renderer: &mut R,
+	) -> Result<(), RenderError> {
+		for (depth, &(start, _, end)) in self.loops.iter().rev().enumerate() {
+			if successor == start {
+				renderer.continue_loop(depth);
+
+				return Ok(());
+			} else if Some(successor) == end {
+				renderer.break_loop(depth);
+
+				return Ok(());
+			}
+		}
+
+		Err(RenderError::Latch { latch, successor })
+	}
+
+	// The latch of a loop selects between its start and its end. Loops without
+	// an end always continue.
+	fn render_latch<R: Render<B, C>>(
+		&self,
+		id: usize,
+		renderer: &mut R,
+	) -> Result<(), RenderError> {
+		let mut successors = self.list.successors(id);
+
+		if let (Some(successor), None) = (successors.next(), successors.next()) {
+			return self.render_jump(id, successor, renderer);
+		}
+
+		renderer.enter_selection();
+
+		for (index, successor) in self.list.successors(id).enumerate() {
+			renderer.enter_arm(self.find_condition(id, index)?);
+
+			self.render_jump(id, successor, renderer)?;
+
+			renderer.leave_arm();
+		}
+
+		renderer.leave_selection();
+
+		Ok(())
+	}
+
+	fn render_node<R: Render<B, C>>(
+		&mut self,
+		node: &Node,
+		renderer: &mut R,
+	) -> Result<(), RenderError> {
+		match node {
+			&Node::Simple(id) => {
+				self.render_statement(id, renderer);
+
+				if self.loops.last().is_some_and(|data| data.1 == id) {
+					self.render_latch(id, renderer)?;
+				}
+			}
+			Node::Sequence(list) => {
+				for node in list {
+					self.render_node(node, renderer)?;
+				}
+			}
+			Node::Branch { head, arms, .. } => {
+				self.render_statement(*head, renderer);
+
+				renderer.enter_selection();
+
+				for (index, arm) in arms.iter().enumerate() {
+					renderer.enter_arm(self.find_condition(*head, index)?);
+
+					self.render_node(arm, renderer)?;
+
+					renderer.leave_arm();
+				}
+
+				renderer.leave_selection();
+			}
+			Node::Loop {
+				start,
+				latch,
+				end,
+				body,
+			} => {
+				renderer.enter_loop();
+
+				self.loops.push((*start, *latch, *end));
+				self.render_node(body, renderer)?;
+				self.loops.pop();
+
+				renderer.leave_loop();
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Restructures the blocks reachable from the entry and renders them.
+	///
+	/// # Errors
+	///
+	/// Returns a description of the first node found that could not be rendered,
+	/// in which case the rendering stops partway through.
+	pub fn render<R: Render<B, C>>(
+		&mut self,
+		entry: usize,
+		renderer: &mut R,
+	) -> Result<(), RenderError> {
+		self.find_reachable(entry);
+
+		let start = self.set_list(entry);
+
+		self.structurer.run(&mut self.list, &mut self.set, start);
+
+		let tree = self.builder.run(
+			&self.list,
+			start,
+			self.structurer.repeat().repetitions(),
+			self.structurer.branch().junctions(),
+		);
+
+		self.loops.clear();
+		self.render_node(&tree, renderer)
+	}
+}
+
+impl<B, C> Default for Relooper<B, C> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
@@ -1,68 +1,456 @@
 use crate::{
-	pass::strongly_connected_finder::StronglyConnectedFinder,
-	set::{Set, Slice},
-	view::{Predecessors, Successors, View},
+	error::StructureError,
+	pass::{
+		depth_first_searcher::DepthFirstSearcher,
+		loop_nesting_finder::LoopNestingFinder,
+		strongly_connected_finder::{StronglyConnected, StronglyConnectedFinder},
+	},
+	set::Set,
+	summary::{Counter, Summary},
+	view::{Successors, View},
 };
 
-use super::single::Single;
+use super::single::{Repetition, Single};
+
+/// A repetition within the loop nesting forest, with the nodes that are part of it,
+/// including those of the repetitions nested in it, and whether it had many entries.
+#[derive(Clone)]
+pub struct Loop {
+	pub repetition: Repetition,
+	pub members: Set,
+	pub parent: Option<usize>,
+	pub depth: usize,
+	pub irreducible: bool,
+}
 
 /// This structure implements a bulk recursive algorithm to restructure a set of nodes.
-/// More details are provided in [`Single`].
-pub struct Bulk {
-	found: Vec<Set>,
+/// More details are provided in [`Single`]. The loop nesting forest is found once by
+/// [`LoopNestingFinder`], using [`StronglyConnectedFinder`] unless another finder is
+/// given, and only the loops whose entries were changed by duplication are found again.
+pub struct Bulk<F = StronglyConnectedFinder> {
+	found: Vec<(usize, Option<usize>)>,
 	pool: Vec<Set>,
+	repetitions: Vec<Repetition>,
+	loops: Vec<Loop>,
+	unreachable: Vec<Set>,
+	reachable: Set,
+	duplicates: Set,
+	depths: Vec<usize>,
+	inside: Vec<usize>,
+	outside: Vec<usize>,
+	start: Option<usize>,
+	new_start: Option<usize>,
+	budget: usize,
+	irreducible_only: bool,
 
 	single: Single,
-	strongly_connected_finder: StronglyConnectedFinder,
+	loop_nesting_finder: LoopNestingFinder<F>,
+	depth_first_searcher: DepthFirstSearcher,
 }
 
 impl Bulk {
 	/// Creates a new instance of the restructurer.
 	#[must_use]
 	pub const fn new() -> Self {
+		Self::with_finder(StronglyConnectedFinder::new())
+	}
+}
+
+impl<F> Bulk<F> {
+	/// Creates a new instance of the restructurer using the given finder
+	/// of strongly connected components.
+	#[must_use]
+	pub const fn with_finder(finder: F) -> Self {
 		Self {
 			found: Vec::new(),
 			pool: Vec::new(),
+			repetitions: Vec::new(),
+			loops: Vec::new(),
+			unreachable: Vec::new(),
+			reachable: Set::new(),
+			duplicates: Set::new(),
+			depths: Vec::new(),
+			inside: Vec::new(),
+			outside: Vec::new(),
+			start: None,
+			new_start: None,
+			budget: 0,
+			irreducible_only: false,
 
 			single: Single::new(),
-			strongly_connected_finder: StronglyConnectedFinder::new(),
+			loop_nesting_finder: LoopNestingFinder::with_finder(finder),
+			depth_first_searcher: DepthFirstSearcher::new(),
 		}
 	}
 
-	fn find_strongly_connected<N: Predecessors + Successors>(&mut self, view: &N, set: Slice) {
-		self.strongly_connected_finder.run(view, set, |list| {
-			let repeats = if let &[first] = list {
-				view.successors(first).any(|id| id == first)
-			} else {
-				!list.is_empty()
-			};
+	/// Returns the repetitions structured by the last run, outermost first.
+	#[must_use]
+	pub fn repetitions(&self) -> &[Repetition] {
+		&self.repetitions
+	}
 
-			if repeats {
-				let mut set = self.pool.pop().unwrap_or_default();
+	/// Returns the loop nesting forest found by the last run, with parents before their
+	/// children and in the same order as [`Bulk::repetitions`].
+	#[must_use]
+	pub fn loops(&self) -> &[Loop] {
+		&self.loops
+	}
+
+	/// Returns the repetitions skipped by the last run since they cannot be reached,
+	/// either from the start if there is one or from outside of them otherwise.
+	#[must_use]
+	pub fn unreachable(&self) -> &[Set] {
+		&self.unreachable
+	}
 
-				set.clear();
-				set.extend(list.iter().copied());
+	/// Returns the node the set is entered at, as given to [`Bulk::set_start`].
+	#[must_use]
+	pub const fn start(&self) -> Option<usize> {
+		self.start
+	}
+
+	/// Returns the node the set is entered at after the last run. The start is moved to
+	/// an assignment leading to the new start of a repetition with many entries containing
+	/// it, and is otherwise left as it was.
+	#[must_use]
+	pub const fn new_start(&self) -> Option<usize> {
+		self.new_start
+	}
+
+	/// Returns the depth of the innermost loop containing the node, starting
+	/// at `1` for outermost loops, or `0` if it is in none.
+	#[must_use]
+	pub fn depth(&self, id: usize) -> usize {
+		self.depths.get(id).copied().unwrap_or(0)
+	}
+
+	/// Sets the node the set is entered at, which is an entry of any repetition it is in
+	/// even without predecessors. Repetitions that cannot be reached from it are left
+	/// untouched, as are those never entered when there is no start. The default is `None`.
+	pub fn set_start(&mut self, start: Option<usize>) {
+		self.start = start;
+	}
+
+	/// Sets the total cost of the nodes that may be duplicated in a run. Repetitions
+	/// with many entries are given a single one by duplicating nodes while the budget
+	/// allows for it, and by adding a selection otherwise. The default is `0`.
+	pub fn set_budget(&mut self, budget: usize) {
+		self.budget = budget;
+	}
+
+	/// Sets whether only repetitions with many entries are restructured, by giving them
+	/// a single start. Repetitions are then left with their exits and latches as they
+	/// are, and those with a single entry are left untouched. The default is `false`.
+	pub fn set_irreducible_only(&mut self, irreducible_only: bool) {
+		self.irreducible_only = irreducible_only;
+	}
 
-				self.found.push(set);
+	pub(crate) fn count_irreducible(&self) -> usize {
+		self.loops.iter().filter(|data| data.irreducible).count()
+	}
+
+	pub(crate) fn pool_mut(&mut self) -> &mut Vec<Set> {
+		&mut self.pool
+	}
+}
+
+impl<F> Bulk<F> {
+	fn find_unentered(&self) -> Option<Vec<usize>> {
+		self.loop_nesting_finder
+			.nests()
+			.iter()
+			.find(|nest| nest.entries.is_empty())
+			.map(|nest| nest.nodes.ascending().collect())
+	}
+
+	fn add_found(&mut self, nest: Option<usize>, first: usize, parent: Option<usize>) {
+		let children = &self.loop_nesting_finder.children(nest)[first..];
+
+		self.found
+			.extend(children.iter().map(|&index| (index, parent)));
+	}
+
+	fn set_depth(&mut self, id: usize, depth: usize) {
+		if self.depths.len() <= id {
+			self.depths.resize(id + 1, 0);
+		}
+
+		self.depths[id] = self.depths[id].max(depth);
+	}
+
+	// Nodes added within a repetition are within all those around it. Other nodes added
+	// while structuring it, such as those entering or leaving it, are only within the
+	// repetitions around it that they lead back into.
+	fn add_to_ancestors<N: Successors>(&mut self, view: &N, mut parent: Option<usize>) {
+		let members = self.single.members();
+
+		self.inside.clear();
+		self.inside.extend_from_slice(members);
+		self.outside.clear();
+		self.outside.extend(
+			self.single
+				.additional()
+				.iter()
+				.filter(|id| !members.contains(id)),
+		);
+
+		while let Some(index) = parent {
+			let data = &mut self.loops[index];
+			let first = self.inside.len();
+
+			data.members.extend(self.inside.iter().copied());
+
+			loop {
+				let len = self.inside.len();
+
+				self.outside.retain(|&id| {
+					let is_inside = view.successors(id).any(|id| data.members.contains(id));
+
+					if is_inside {
+						data.members.grow_insert(id);
+						self.inside.push(id);
+					}
+
+					!is_inside
+				});
+
+				if self.inside.len() == len {
+					break;
+				}
 			}
+
+			let depth = data.depth;
+
+			parent = data.parent;
+
+			for index in first..self.inside.len() {
+				self.set_depth(self.inside[index], depth);
+			}
+		}
+	}
+
+	fn add_loop<N: Successors>(
+		&mut self,
+		view: &N,
+		repetition: Repetition,
+		nest: usize,
+		parent: Option<usize>,
+	) {
+		let mut members = self.pool.pop().unwrap_or_default();
+
+		members.clone_from(&self.loop_nesting_finder.nests()[nest].nodes);
+		members.extend(self.single.members().iter().copied());
+
+		let depth = parent.map_or(1, |index| self.loops[index].depth + 1);
+
+		for id in members.ascending() {
+			self.set_depth(id, depth);
+		}
+
+		self.add_to_ancestors(view, parent);
+		self.loops.push(Loop {
+			repetition,
+			members,
+			parent,
+			depth,
+			irreducible: self.single.is_irreducible(),
 		});
 	}
 
-	/// Restructures the nodes in the given set.
-	pub fn run<N: View>(&mut self, view: &mut N, set: &mut Set) {
-		self.find_strongly_connected(view, set.as_slice());
+	fn find_reachable<N: View>(&mut self, view: &N, set: &Set) {
+		self.reachable.clear();
+
+		if let Some(start) = self.start {
+			self.depth_first_searcher.nodes_mut().clone_from(set);
+			self.depth_first_searcher.run(view, start, |id, post| {
+				if !post {
+					self.reachable.grow_insert(id);
+				}
+			});
+		} else {
+			self.reachable.clone_from(set);
+		}
+	}
+
+	// Repetitions are either wholly reachable or not at all, so checking any of their
+	// entries is enough. Without a start, only those never entered are unreachable.
+	fn is_reachable(&self, nest: usize) -> bool {
+		let entries = &self.loop_nesting_finder.nests()[nest].entries;
+
+		entries
+			.first()
+			.is_some_and(|&id| self.reachable.contains(id))
+	}
+
+	// The loops nested in a repetition are those left once its entries are removed, so
+	// they only change when duplication leaves it with fewer entries than it had.
+	fn set_nested<N>(&mut self, view: &N, nest: usize, start: usize)
+	where
+		N: View,
+		F: StronglyConnected<N>,
+	{
+		let data = &self.loop_nesting_finder.nests()[nest];
+
+		if self.single.entries() == data.entries {
+			return;
+		}
 
-		while let Some(mut child) = self.found.pop() {
-			let start = self.single.run(view, child.as_slice());
+		let mut child = self.pool.pop().unwrap_or_default();
 
-			child.remove(start);
+		child.clone_from(&data.nodes);
+		child.remove(start);
 
-			self.find_strongly_connected(view, child.as_slice());
+		self.loop_nesting_finder
+			.set_nested(view, nest, child.as_slice());
+
+		self.pool.push(child);
+	}
+
+	// Loops are found through the view within the counter, which the finder is bound on.
+	fn run_found<N>(&mut self, view: &mut Counter<N>, set: &mut Set)
+	where
+		N: View,
+		F: StronglyConnected<N>,
+	{
+		let mut budget = self.budget;
+
+		self.repetitions.clear();
+		self.depths.clear();
+		self.pool
+			.extend(self.loops.drain(..).map(|data| data.members));
+
+		self.pool.append(&mut self.unreachable);
+		self.single.set_start(self.start);
+		self.find_reachable(view, set);
+		self.add_found(None, 0, None);
+
+		while let Some((nest, parent)) = self.found.pop() {
+			let nodes = self.loop_nesting_finder.nests()[nest].nodes.as_slice();
+
+			// Nothing runs a repetition that is never reached, so it is left as it is.
+			if !self.is_reachable(nest) {
+				let mut unreachable = self.pool.pop().unwrap_or_default();
+
+				unreachable.clone_from_slice(nodes);
+
+				self.unreachable.push(unreachable);
+
+				continue;
+			}
+
+			// Copies of the latch of an outer repetition would also repeat it.
+			let allowed = if self
+				.repetitions
+				.iter()
+				.any(|r| r.latch.is_some_and(|latch| nodes.contains(latch)))
+			{
+				0
+			} else {
+				budget
+			};
+
+			let repetition = if self.irreducible_only {
+				self.single.run_entries(view, nodes, allowed)
+			} else {
+				self.single.run(view, nodes, allowed)
+			};
+			let index = self.loops.len();
+
+			self.add_loop(view, repetition, nest, parent);
+			self.repetitions.push(repetition);
+
+			self.set_nested(view.view(), nest, repetition.start);
+			self.add_found(Some(nest), 0, Some(index));
+
+			// Duplicated nodes are outside of the repetition but may repeat among themselves.
+			if !self.single.duplicates().is_empty() {
+				let duplicates = self.single.duplicates();
+				let outer = self.loop_nesting_finder.nests()[nest].parent;
+				let first = self.loop_nesting_finder.children(outer).len();
+
+				budget -= self.single.cost();
+
+				self.duplicates.clear();
+				self.duplicates.extend(duplicates.iter().copied());
+
+				self.loop_nesting_finder
+					.add_nested(view.view(), outer, self.duplicates.as_slice());
+
+				self.add_found(outer, first, parent);
+			}
 
 			set.extend(self.single.additional().iter().copied());
 
-			self.pool.push(child);
+			self.reachable
+				.extend(self.single.additional().iter().copied());
+		}
+
+		self.new_start = self.single.start();
+	}
+
+	pub(crate) fn run_counted<N>(&mut self, view: &mut N, set: &mut Set)
+	where
+		N: View,
+		F: StronglyConnected<N>,
+	{
+		self.loop_nesting_finder
+			.run(view, set.as_slice(), self.start);
+		self.run_found(&mut Counter::new(view), set);
+	}
+
+	fn run_summarized<N>(&mut self, view: &mut N, set: &mut Set) -> Summary
+	where
+		N: View,
+		F: StronglyConnected<N>,
+	{
+		let mut counter = Counter::new(view);
+
+		self.run_found(&mut counter, set);
+
+		let exit = Summary::find_exit(counter.view(), set.as_slice());
+
+		Summary {
+			exit,
+			start: self.new_start.filter(|&id| Some(id) != self.start),
+			irreducible: self.count_irreducible(),
+			unreachable: self.unreachable.len(),
+			..counter.into_summary()
+		}
+	}
+
+	/// Validates that every repetition in the given set is entered from outside of it
+	/// or contains the start, then restructures the nodes as [`Bulk::run`] does.
+	///
+	/// # Errors
+	///
+	/// Returns the first repetition never entered, in which case nothing is changed.
+	pub fn try_run<N>(&mut self, view: &mut N, set: &mut Set) -> Result<Summary, StructureError>
+	where
+		N: View,
+		F: StronglyConnected<N>,
+	{
+		self.loop_nesting_finder
+			.run(view, set.as_slice(), self.start);
+
+		if let Some(region) = self.find_unentered() {
+			return Err(StructureError::Unentered { region });
 		}
+
+		Ok(self.run_summarized(view, set))
+	}
+
+	/// Restructures the nodes in the given set and returns a summary of the changes.
+	/// Repetitions never entered are left as they are and recorded in [`Bulk::unreachable`],
+	/// where [`Bulk::try_run`] would report them as an error instead.
+	pub fn run<N>(&mut self, view: &mut N, set: &mut Set) -> Summary
+	where
+		N: View,
+		F: StronglyConnected<N>,
+	{
+		self.loop_nesting_finder
+			.run(view, set.as_slice(), self.start);
+
+		self.run_summarized(view, set)
 	}
 }
 
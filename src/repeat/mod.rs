@@ -1,4 +1,5 @@
 mod bulk;
 mod single;
 
-pub use bulk::Bulk as Repeat;
+pub use bulk::{Bulk as Repeat, Loop};
+pub use single::Repetition;
@@ -1,17 +1,50 @@
 use crate::{
+	pass::{depth_first_searcher::DepthFirstSearcher, duplicator::Duplicator},
 	set::Slice,
 	view::{Flag, Predecessors, Successors, View},
 };
 
+/// A structured repetition, with the nodes which start, repeat, and leave it
+/// as they were when the repetition was structured. The latch and end are
+/// only set when the repetition was given a single one of each, so one with
+/// a latch but no end never leaves.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Repetition {
+	pub start: usize,
+	pub latch: Option<usize>,
+	pub end: Option<usize>,
+}
+
+impl Repetition {
+	/// Returns whether the repetition has no exit, so its latch only goes back to the start.
+	#[must_use]
+	pub const fn is_infinite(&self) -> bool {
+		self.latch.is_some() && self.end.is_none()
+	}
+}
+
 /// This structure implements a single pass of this algorithm. It assumes that the set
-/// provided is a strongly connected component and that there is at least one edge
-/// from outside the set coming in.
+/// provided is a strongly connected component and that it is entered, either by an edge
+/// from outside the set coming in or by containing the start. When allowed a budget,
+/// repetitions with many entries are split by duplicating the nodes reached from all
+/// but the first entry, instead of adding a selection on [`Flag::C`]. A set without
+/// exits is left without an end, and its latch only goes back to the start.
 pub struct Single {
+	start: Option<usize>,
 	entries: Vec<usize>,
 	exits: Vec<usize>,
+	regions: Vec<usize>,
+	bounds: Vec<usize>,
 
 	additional: Vec<usize>,
+	members: Vec<usize>,
+	duplicates: Vec<usize>,
 	temporaries: Vec<usize>,
+	irreducible: bool,
+	cost: usize,
+
+	depth_first_searcher: DepthFirstSearcher,
+	duplicator: Duplicator,
 }
 
 impl Single {
@@ -19,26 +52,80 @@ impl Single {
 	#[must_use]
 	pub const fn new() -> Self {
 		Self {
+			start: None,
 			entries: Vec::new(),
 			exits: Vec::new(),
+			regions: Vec::new(),
+			bounds: Vec::new(),
 
 			additional: Vec::new(),
+			members: Vec::new(),
+			duplicates: Vec::new(),
 			temporaries: Vec::new(),
+			irreducible: false,
+			cost: 0,
+
+			depth_first_searcher: DepthFirstSearcher::new(),
+			duplicator: Duplicator::new(),
 		}
 	}
 
+	/// Sets the node the graph is entered at, which is an entry of any repetition it is in.
+	pub fn set_start(&mut self, start: Option<usize>) {
+		self.start = start;
+	}
+
+	/// Returns the node the graph is entered at, which is replaced by an assignment
+	/// leading to the new start of a repetition when it is one of many entries.
+	#[must_use]
+	pub const fn start(&self) -> Option<usize> {
+		self.start
+	}
+
+	/// Returns the entries left to the repetition by the restructuring, before any
+	/// selection was added to give it a single start.
+	#[must_use]
+	pub fn entries(&self) -> &[usize] {
+		&self.entries
+	}
+
 	/// Returns the additional nodes created by the restructuring.
 	#[must_use]
 	pub fn additional(&self) -> &[usize] {
 		&self.additional
 	}
 
+	/// Returns the additional nodes created by the restructuring that are part of the repetition.
+	#[must_use]
+	pub fn members(&self) -> &[usize] {
+		&self.members
+	}
+
+	/// Returns the nodes duplicated by the restructuring, which may repeat themselves.
+	#[must_use]
+	pub fn duplicates(&self) -> &[usize] {
+		&self.duplicates
+	}
+
+	/// Returns the cost of the nodes duplicated by the restructuring, as charged against
+	/// the budget.
+	#[must_use]
+	pub const fn cost(&self) -> usize {
+		self.cost
+	}
+
+	/// Returns whether the repetition had many entries before the restructuring.
+	#[must_use]
+	pub const fn is_irreducible(&self) -> bool {
+		self.irreducible
+	}
+
 	fn find_entries_and_exits<N: Predecessors + Successors>(&mut self, view: &N, set: Slice) {
 		self.entries.clear();
 		self.exits.clear();
 
 		for id in set {
-			if view.predecessors(id).any(|id| !set.contains(id)) {
+			if self.start == Some(id) || view.predecessors(id).any(|id| !set.contains(id)) {
 				self.entries.push(id);
 			}
 
@@ -50,10 +137,93 @@ impl Single {
 		self.exits.dedup();
 	}
 
-	fn set_new_start<N: View>(&mut self, view: &mut N) -> usize {
+	fn find_regions<N: View>(&mut self, view: &N, set: Slice, primary: usize) -> usize {
+		let mut cost = 0;
+
+		self.regions.clear();
+		self.bounds.clear();
+
+		for &entry in &self.entries[1..] {
+			let nodes = self.depth_first_searcher.nodes_mut();
+
+			nodes.clone_from_slice(set);
+			nodes.remove(primary);
+
+			self.depth_first_searcher.run(view, entry, |id, post| {
+				if !post {
+					cost += view.cost(id);
+
+					self.regions.push(id);
+				}
+			});
+
+			self.bounds.push(self.regions.len());
+		}
+
+		cost
+	}
+
+	// The region of every entry but the first is searched from the entry itself,
+	// so it is always copied along with it.
+	fn set_region_copy<N: View>(&mut self, view: &mut N, set: Slice, entry: usize) {
+		let copy = self
+			.duplicator
+			.copy_of(entry)
+			.expect("the region of an entry should contain it");
+
+		self.temporaries.clear();
+		self.temporaries
+			.extend(view.predecessors(entry).filter(|&id| !set.contains(id)));
+
+		for &predecessor in &self.temporaries {
+			view.replace_edge(predecessor, entry, copy);
+		}
+
+		self.duplicates.extend(self.duplicator.copies());
+	}
+
+	// Every entry other than the first gets its own copy of the nodes it reaches
+	// without going through the first entry, which is then the only one left.
+	fn set_split_entries<N: View>(&mut self, view: &mut N, set: Slice, budget: usize) -> bool {
+		// The start cannot be copied as nothing leads to it, so it is always the first entry.
+		if let Some(index) = self.entries.iter().position(|&id| Some(id) == self.start) {
+			self.entries[..=index].rotate_right(1);
+		}
+
+		let Some(&primary) = self.entries.first() else {
+			return false;
+		};
+
+		// Nothing is changed unless every region can be copied within the budget.
+		let cost = self.find_regions(view, set, primary);
+
+		if cost >= budget || !Duplicator::can_run(view, &self.regions) {
+			return false;
+		}
+
+		let mut start = 0;
+
+		for index in 1..self.entries.len() {
+			let end = self.bounds[index - 1];
+
+			self.duplicator.run(view, &self.regions[start..end]);
+			self.set_region_copy(view, set, self.entries[index]);
+
+			start = end;
+		}
+
+		self.additional.extend_from_slice(&self.duplicates);
+		self.entries.truncate(1);
+		self.cost = cost;
+
+		true
+	}
+
+	fn set_new_start<N: View>(&mut self, view: &mut N, set: Slice) -> usize {
 		let start = view.add_selection(Flag::C);
 
 		self.additional.push(start);
+		self.members.push(start);
 
 		for (index, &entry) in self.entries.iter().enumerate() {
 			self.temporaries.clear();
@@ -66,6 +236,20 @@ impl Single {
 				view.add_edge(branch, start);
 
 				self.additional.push(branch);
+
+				if set.contains(predecessor) {
+					self.members.push(branch);
+				}
+			}
+
+			// The graph is now entered at an assignment, just as if it had led to the start.
+			if self.start == Some(entry) {
+				let branch = view.add_assignment(Flag::C, index);
+
+				view.add_edge(branch, start);
+
+				self.additional.push(branch);
+				self.start = Some(branch);
 			}
 
 			view.add_edge(start, entry);
@@ -74,11 +258,11 @@ impl Single {
 		start
 	}
 
-	fn find_or_set_start<N: View>(&mut self, view: &mut N) -> usize {
+	fn find_or_set_start<N: View>(&mut self, view: &mut N, set: Slice) -> usize {
 		if let &[start] = self.entries.as_slice() {
 			start
 		} else {
-			self.set_new_start(view)
+			self.set_new_start(view, set)
 		}
 	}
 
@@ -99,6 +283,7 @@ impl Single {
 				view.add_edge(branch, end);
 
 				self.additional.push(branch);
+				self.members.push(branch);
 			}
 
 			view.add_edge(end, exit);
@@ -107,11 +292,11 @@ impl Single {
 		end
 	}
 
-	fn find_or_set_end<N: View>(&mut self, view: &mut N, set: Slice) -> usize {
-		if let &[end] = self.exits.as_slice() {
-			end
-		} else {
-			self.set_new_end(view, set)
+	fn find_or_set_end<N: View>(&mut self, view: &mut N, set: Slice) -> Option<usize> {
+		match *self.exits.as_slice() {
+			[] => None,
+			[end] => Some(end),
+			_ => Some(self.set_new_end(view, set)),
 		}
 	}
 
@@ -125,24 +310,37 @@ impl Single {
 		parent != id && Self::in_set_or_inserted(view, set, id)
 	}
 
-	fn has_one_latch<N: Predecessors>(view: &N, set: Slice, start: usize, end: usize) -> bool {
+	fn find_one_latch<N: Predecessors + Successors>(
+		view: &N,
+		set: Slice,
+		start: usize,
+		end: Option<usize>,
+	) -> Option<usize> {
 		let mut repetitions = view
 			.predecessors(start)
 			.filter(|&id| Self::in_set_or_inserted(view, set, id));
 
+		let (Some(repetition), None) = (repetitions.next(), repetitions.next()) else {
+			return None;
+		};
+
+		// Without an end, the latch must be the one node going back to the start
+		// and must go nowhere else.
+		let Some(end) = end else {
+			return view
+				.successors(repetition)
+				.all(|id| id == start)
+				.then_some(repetition);
+		};
+
 		let mut exits = view
 			.predecessors(end)
 			.filter(|&id| Self::in_set_acyclic(view, set, end, id));
 
-		matches!(
-			(
-				repetitions.next(),
-				repetitions.next(),
-				exits.next(),
-				exits.next()
-			),
-			(Some(repetition), None, Some(exit), None) if repetition == exit
-		)
+		match (exits.next(), exits.next()) {
+			(Some(exit), None) if repetition == exit => Some(repetition),
+			_ => None,
+		}
 	}
 
 	fn set_break<N: View>(&mut self, view: &mut N, set: Slice, latch: usize, end: usize) {
@@ -159,6 +357,7 @@ impl Single {
 			view.add_edge(branch, latch);
 
 			self.additional.push(branch);
+			self.members.push(branch);
 		}
 	}
 
@@ -176,36 +375,104 @@ impl Single {
 			view.add_edge(branch, latch);
 
 			self.additional.push(branch);
+			self.members.push(branch);
 		}
 	}
 
-	fn set_new_latch<N: View>(&mut self, view: &mut N, set: Slice, start: usize, end: usize) {
+	// A repetition without an end has no break path, so its latch needs no selection
+	// and the nodes going back to the start lead to it directly.
+	fn set_new_repeat<N: View>(&mut self, view: &mut N, set: Slice, start: usize) -> usize {
+		let latch = view.add_no_operation();
+
+		self.additional.push(latch);
+		self.members.push(latch);
+
+		self.temporaries.clear();
+		self.temporaries.extend(
+			view.predecessors(start)
+				.filter(|&id| Self::in_set_or_inserted(view, set, id)),
+		);
+
+		for &entry in &self.temporaries {
+			view.replace_edge(entry, start, latch);
+		}
+
+		view.add_edge(latch, start);
+
+		latch
+	}
+
+	fn set_new_latch<N: View>(
+		&mut self,
+		view: &mut N,
+		set: Slice,
+		start: usize,
+		end: Option<usize>,
+	) -> usize {
+		let Some(end) = end else {
+			return self.set_new_repeat(view, set, start);
+		};
+
 		let latch = view.add_selection(Flag::B);
 
 		self.additional.push(latch);
+		self.members.push(latch);
 
 		self.set_break(view, set, latch, end);
 		self.set_continue(view, set, latch, start);
 
 		view.add_edge(latch, end);
 		view.add_edge(latch, start);
+
+		latch
 	}
 
-	/// Applies the restructuring algorithm to the given set of nodes.
-	/// The start node of the structured repetition is returned.
-	pub fn run<N: View>(&mut self, view: &mut N, set: Slice) -> usize {
+	fn find_start<N: View>(&mut self, view: &mut N, set: Slice, budget: usize) -> usize {
 		self.find_entries_and_exits(view, set);
 
 		self.additional.clear();
+		self.members.clear();
+		self.duplicates.clear();
+		self.irreducible = self.entries.len() > 1;
+		self.cost = 0;
 
-		let start = self.find_or_set_start(view);
-		let end = self.find_or_set_end(view, set);
+		if self.irreducible && budget != 0 {
+			self.set_split_entries(view, set, budget);
+		}
+
+		self.find_or_set_start(view, set)
+	}
+
+	/// Applies only the first part of the restructuring algorithm to the given set of
+	/// nodes, giving it a single start but leaving its exits and latches untouched.
+	/// The start node of the repetition is returned, without a latch or end.
+	pub fn run_entries<N: View>(&mut self, view: &mut N, set: Slice, budget: usize) -> Repetition {
+		let start = self.find_start(view, set, budget);
 
-		if !Self::has_one_latch(view, set, start, end) {
-			self.set_new_latch(view, set, start, end);
+		Repetition {
+			start,
+			latch: None,
+			end: None,
 		}
+	}
 
-		start
+	/// Applies the restructuring algorithm to the given set of nodes, duplicating
+	/// nodes only when their cost is under the budget.
+	/// The start, latch, and end nodes of the structured repetition are returned,
+	/// with no end if the set has no exits and so repeats forever.
+	pub fn run<N: View>(&mut self, view: &mut N, set: Slice, budget: usize) -> Repetition {
+		let start = self.find_start(view, set, budget);
+		let end = self.find_or_set_end(view, set);
+		let latch = match Self::find_one_latch(view, set, start, end) {
+			Some(latch) => latch,
+			None => self.set_new_latch(view, set, start, end),
+		};
+
+		Repetition {
+			start,
+			latch: Some(latch),
+			end,
+		}
 	}
 }
 
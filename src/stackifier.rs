@@ -0,0 +1,312 @@
+use crate::{tree::Node, view::Successors};
+
+/// A WebAssembly control instruction, or the code of a node of the graph.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Instruction {
+	/// Opens a block, whose label is its end.
+	Block,
+
+	/// Opens a loop, whose label is its start.
+	Loop,
+
+	/// Closes the innermost block or loop.
+	End,
+
+	/// The code of a node. Nodes with many successors, including selections,
+	/// leave the index of the successor taken on the stack.
+	Node(usize),
+
+	/// Jumps to the label at the given depth.
+	Br(usize),
+
+	/// Jumps to the label at the given depth if the value on the stack is not zero.
+	BrIf(usize),
+
+	/// Jumps to the label at the depth indexed by the value on the stack,
+	/// or to the default one if it is out of bounds.
+	BrTable { targets: Vec<usize>, default: usize },
+
+	/// Replaces the value on the stack by whether it is zero.
+	Eqz,
+}
+
+// A loop being written, with the depth of its label and whether it is in a block
+// whose label is its end.
+struct Label {
+	start: usize,
+	latch: usize,
+	end: Option<usize>,
+	depth: usize,
+	has_block: bool,
+}
+
+/// This structure turns a structured control tree, such as one built from a graph
+/// restructured by both [`Repeat`] and [`Branch`], into a linear sequence of
+/// WebAssembly instructions with relative label depths.
+///
+/// [`Repeat`]: crate::repeat::Repeat
+/// [`Branch`]: crate::branch::Branch
+pub struct Stackifier {
+	instructions: Vec<Instruction>,
+	loops: Vec<Label>,
+	depth: usize,
+}
+
+impl Stackifier {
+	/// Creates a new instance of the stackifier.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			instructions: Vec::new(),
+			loops: Vec::new(),
+			depth: 0,
+		}
+	}
+
+	/// Returns the instructions produced by the last run.
+	#[must_use]
+	pub fn instructions(&self) -> &[Instruction] {
+		&self.instructions
+	}
+
+	fn add_open(&mut self, instruction: Instruction) {
+		self.instructions.push(instruction);
+		self.depth += 1;
+	}
+
+	fn add_end(&mut self) {
+		self.instructions.push(Instruction::End);
+		self.depth -= 1;
+	}
+
+	const fn is_empty(node: &Node) -> bool {
+		matches!(node, Node::Sequence(list) if list.is_empty())
+	}
+
+	// Two arms need no table, and an arm that is empty can leave straight to the
+	// continuation, while the other is written in the same block.
+	fn add_branch_if<N: Successors>(&mut self, view: &N, head: usize, arms: &[Node]) {
+		let [first, second] = arms else {
+			unreachable!("branch {head} does not have two arms")
+		};
+
+		self.add_open(Instruction::Block);
+
+		if Self::is_empty(second) || Self::is_empty(first) {
+			self.instructions.push(Instruction::Node(head));
+
+			let arm = if Self::is_empty(second) {
+				first
+			} else {
+				self.instructions.push(Instruction::Eqz);
+
+				second
+			};
+
+			self.instructions.push(Instruction::BrIf(0));
+
+			self.add_node(view, arm);
+		} else {
+			self.add_open(Instruction::Block);
+
+			self.instructions.push(Instruction::Node(head));
+			self.instructions.push(Instruction::BrIf(0));
+
+			self.add_node(view, first);
+
+			self.instructions.push(Instruction::Br(1));
+
+			self.add_end();
+			self.add_node(view, second);
+		}
+
+		self.add_end();
+	}
+
+	// Each arm follows the end of its own block, with the first arm innermost, and
+	// every arm but the last then leaves to the end of the outermost block.
+	fn add_branch_table<N: Successors>(&mut self, view: &N, head: usize, arms: &[Node]) {
+		let last = arms.len() - 1;
+
+		for _ in 0..=arms.len() {
+			self.add_open(Instruction::Block);
+		}
+
+		self.instructions.push(Instruction::Node(head));
+		self.instructions.push(Instruction::BrTable {
+			targets: (0..last).collect(),
+			default: last,
+		});
+
+		for (index, arm) in arms.iter().enumerate() {
+			self.add_end();
+			self.add_node(view, arm);
+
+			if index != last {
+				self.instructions.push(Instruction::Br(last - index));
+			}
+		}
+
+		self.add_end();
+	}
+
+	fn add_branch<N: Successors>(&mut self, view: &N, head: usize, arms: &[Node]) {
+		match arms.len() {
+			0 | 1 => {
+				self.instructions.push(Instruction::Node(head));
+
+				for arm in arms {
+					self.add_node(view, arm);
+				}
+			}
+			2 => self.add_branch_if(view, head, arms),
+			_ => self.add_branch_table(view, head, arms),
+		}
+	}
+
+	// Jumps to the start of a loop go to its label, and those to the end of a loop go
+	// to the label of its block, while the end of the innermost loop may be fallen
+	// through to instead, which is `None`.
+	fn find_target(&self, id: usize) -> Option<usize> {
+		for (index, label) in self.loops.iter().rev().enumerate() {
+			if id == label.start {
+				return Some(self.depth - label.depth);
+			} else if Some(id) != label.end {
+				continue;
+			} else if label.has_block {
+				return Some(self.depth - label.depth + 1);
+			} else if index == 0 {
+				return None;
+			}
+		}
+
+		None
+	}
+
+	// The latch goes back to the start of a loop around it or falls through to
+	// the end of its own, which needs a block around the loop unless a single
+	// conditional branch can choose between them.
+	fn add_latch<N: Successors>(&mut self, view: &N, latch: usize) {
+		let mut successors = view.successors(latch).map(|id| self.find_target(id));
+		let targets = (successors.next(), successors.next(), successors.next());
+
+		self.instructions.push(Instruction::Node(latch));
+
+		match targets {
+			(Some(None), None, _) | (None, _, _) => {}
+			(Some(Some(depth)), None, _) => self.instructions.push(Instruction::Br(depth)),
+			(Some(None), Some(Some(depth)), None) => {
+				self.instructions.push(Instruction::BrIf(depth));
+			}
+			(Some(Some(depth)), Some(None), None) => {
+				self.instructions.push(Instruction::Eqz);
+				self.instructions.push(Instruction::BrIf(depth));
+			}
+			(Some(Some(first)), Some(Some(second)), None) => {
+				self.instructions.push(Instruction::BrIf(second));
+				self.instructions.push(Instruction::Br(first));
+			}
+			_ => {
+				let block =
+					self.depth - self.loops.last().map_or(self.depth, |label| label.depth) + 1;
+				let mut targets: Vec<_> = view
+					.successors(latch)
+					.map(|id| self.find_target(id).unwrap_or(block))
+					.collect();
+
+				let default = targets.pop().unwrap_or_default();
+
+				self.instructions
+					.push(Instruction::BrTable { targets, default });
+			}
+		}
+	}
+
+	// The loop needs a block around it when its latch has many successors
+	// that cannot be told apart by a single conditional branch.
+	fn has_block<N: Successors>(&self, view: &N, start: usize, latch: usize) -> bool {
+		let is_start = |id| id == start || self.loops.iter().any(|label| label.start == id);
+		let mut successors = view.successors(latch);
+
+		match (successors.next(), successors.next(), successors.next()) {
+			(Some(first), Some(second), None) => !is_start(first) && !is_start(second),
+			(_, _, third) => third.is_some(),
+		}
+	}
+
+	fn add_loop<N: Successors>(
+		&mut self,
+		view: &N,
+		start: usize,
+		latch: usize,
+		end: Option<usize>,
+		body: &Node,
+	) {
+		let has_block = self.has_block(view, start, latch);
+
+		if has_block {
+			self.add_open(Instruction::Block);
+		}
+
+		self.add_open(Instruction::Loop);
+		self.loops.push(Label {
+			start,
+			latch,
+			end,
+			depth: self.depth,
+			has_block,
+		});
+
+		self.add_node(view, body);
+
+		self.loops.pop();
+		self.add_end();
+
+		if has_block {
+			self.add_end();
+		}
+	}
+
+	fn add_simple<N: Successors>(&mut self, view: &N, id: usize) {
+		if self.loops.last().is_some_and(|label| label.latch == id) {
+			self.add_latch(view, id);
+		} else {
+			self.instructions.push(Instruction::Node(id));
+		}
+	}
+
+	fn add_node<N: Successors>(&mut self, view: &N, node: &Node) {
+		match node {
+			&Node::Simple(id) => self.add_simple(view, id),
+			Node::Sequence(list) => {
+				for node in list {
+					self.add_node(view, node);
+				}
+			}
+			Node::Branch { head, arms, .. } => self.add_branch(view, *head, arms),
+			Node::Loop {
+				start,
+				latch,
+				end,
+				body,
+			} => self.add_loop(view, *start, *latch, *end, body),
+		}
+	}
+
+	/// Produces the instructions of the tree and returns them.
+	pub fn run<N: Successors>(&mut self, view: &N, tree: &Node) -> &[Instruction] {
+		self.instructions.clear();
+		self.loops.clear();
+		self.depth = 0;
+
+		self.add_node(view, tree);
+
+		&self.instructions
+	}
+}
+
+impl Default for Stackifier {
+	fn default() -> Self {
+		Self::new()
+	}
+}
@@ -0,0 +1,130 @@
+use crate::{
+	branch::Branch,
+	pass::{depth_first_searcher::DepthFirstSearcher, exit_unifier::ExitUnifier},
+	repeat::Repeat,
+	set::Set,
+	summary::{Counter, Summary},
+	view::{Successors, View},
+};
+
+/// This structure runs the whole pipeline on a set of nodes. Repetitions are
+/// structured first by [`Repeat`], the exits are then joined by [`ExitUnifier`],
+/// and the resulting acyclic regions are structured by [`Branch`] from the start.
+/// Apart from the start, nodes of the set should only be reached from within it.
+/// Nodes that cannot be reached from the start are left untouched, but they should
+/// not lead to those that can.
+pub struct Structurer {
+	pool: Vec<Set>,
+
+	repeat: Repeat,
+	branch: Branch,
+	exit_unifier: ExitUnifier,
+	depth_first_searcher: DepthFirstSearcher,
+}
+
+impl Structurer {
+	/// Creates a new instance of the restructurer.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			pool: Vec::new(),
+
+			repeat: Repeat::new(),
+			branch: Branch::new(),
+			exit_unifier: ExitUnifier::new(),
+			depth_first_searcher: DepthFirstSearcher::new(),
+		}
+	}
+
+	/// Returns the [`Repeat`] used, with what it recorded in the last run.
+	#[must_use]
+	pub const fn repeat(&self) -> &Repeat {
+		&self.repeat
+	}
+
+	/// Returns the [`Branch`] used, with what it recorded in the last run.
+	#[must_use]
+	pub const fn branch(&self) -> &Branch {
+		&self.branch
+	}
+
+	/// Sets the total cost of the nodes that may be duplicated by each of
+	/// [`Repeat`] and [`Branch`] in a run. The default is `0`.
+	pub fn set_budget(&mut self, budget: usize) {
+		self.repeat.set_budget(budget);
+		self.branch.set_budget(budget);
+	}
+
+	// Returns the start, which may have been moved before a repetition containing it,
+	// and the exit of the nodes reachable from it.
+	fn run_counted<N: View>(
+		&mut self,
+		view: &mut N,
+		set: &mut Set,
+		start: usize,
+	) -> (usize, Option<usize>) {
+		std::mem::swap(&mut self.pool, self.repeat.pool_mut());
+
+		self.repeat.set_start(Some(start));
+		self.repeat.run_counted(view, set);
+
+		std::mem::swap(&mut self.pool, self.repeat.pool_mut());
+
+		let start = self.repeat.new_start().unwrap_or(start);
+		let mut reachable = self.pool.pop().unwrap_or_default();
+
+		self.find_reachable(view, set, start, &mut reachable);
+		let exit = self.exit_unifier.run(view, &mut reachable);
+
+		std::mem::swap(&mut self.pool, self.branch.pool_mut());
+
+		self.branch.run_counted(view, &mut reachable, start);
+
+		std::mem::swap(&mut self.pool, self.branch.pool_mut());
+
+		set.extend(reachable.ascending());
+
+		self.pool.push(reachable);
+
+		(start, exit)
+	}
+
+	fn find_reachable<N: Successors>(
+		&mut self,
+		view: &N,
+		set: &Set,
+		start: usize,
+		reachable: &mut Set,
+	) {
+		reachable.clear();
+
+		self.depth_first_searcher.nodes_mut().clone_from(set);
+		self.depth_first_searcher.run(view, start, |id, post| {
+			if !post {
+				reachable.grow_insert(id);
+			}
+		});
+	}
+
+	/// Restructures the nodes in the given set from the start and returns a summary
+	/// of the changes, with the exit of the nodes reachable from the start if they
+	/// have one and the new start if it was moved.
+	pub fn run<N: View>(&mut self, view: &mut N, set: &mut Set, start: usize) -> Summary {
+		let mut counter = Counter::new(view);
+		let (moved, exit) = self.run_counted(&mut counter, set, start);
+
+		Summary {
+			exit,
+			start: (moved != start).then_some(moved),
+			irreducible: self.repeat.count_irreducible(),
+			unreachable: self.repeat.unreachable().len(),
+			..counter.into_summary()
+		}
+	}
+}
+
+impl Default for Structurer {
+	fn default() -> Self {
+		Self::new()
+	}
+}
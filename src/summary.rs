@@ -0,0 +1,146 @@
+use crate::{
+	set::Slice,
+	view::{Flag, Predecessors, Successors, View},
+};
+
+/// A summary of the changes made by a restructuring pass.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Summary {
+	/// Whether the graph was changed at all.
+	pub changed: bool,
+
+	/// The number of no-operation nodes added.
+	pub no_operations: usize,
+
+	/// The number of selection nodes added.
+	pub selections: usize,
+
+	/// The number of assignment nodes added.
+	pub assignments: usize,
+
+	/// The number of nodes duplicated.
+	pub duplicates: usize,
+
+	/// The number of repetitions found with many entries, which are those marked
+	/// as irreducible in [`Repeat::loops`].
+	///
+	/// [`Repeat::loops`]: crate::repeat::Repeat::loops
+	pub irreducible: usize,
+
+	/// The number of repetitions left untouched since they cannot be reached, as
+	/// listed in [`Repeat::unreachable`].
+	///
+	/// [`Repeat::unreachable`]: crate::repeat::Repeat::unreachable
+	pub unreachable: usize,
+
+	/// The node the region is now entered at, when an assignment was added before
+	/// a repetition with many entries that contained the start.
+	pub start: Option<usize>,
+
+	/// The only node of the region with no successors in it, if there is one.
+	pub exit: Option<usize>,
+}
+
+impl Summary {
+	/// Returns the total number of nodes added.
+	#[must_use]
+	pub const fn added(&self) -> usize {
+		self.no_operations + self.selections + self.assignments + self.duplicates
+	}
+
+	pub(crate) fn find_exit<N: Successors>(view: &N, set: Slice) -> Option<usize> {
+		let mut exits = set
+			.into_iter()
+			.filter(|&id| !view.successors(id).any(|id| set.contains(id)));
+
+		match (exits.next(), exits.next()) {
+			(Some(exit), None) => Some(exit),
+			_ => None,
+		}
+	}
+}
+
+// A view that counts the nodes added through it.
+pub(crate) struct Counter<'a, N> {
+	view: &'a mut N,
+	summary: Summary,
+}
+
+impl<'a, N> Counter<'a, N> {
+	pub fn new(view: &'a mut N) -> Self {
+		Self {
+			view,
+			summary: Summary::default(),
+		}
+	}
+
+	pub fn view(&self) -> &N {
+		self.view
+	}
+
+	pub fn into_summary(self) -> Summary {
+		let mut summary = self.summary;
+
+		summary.changed = summary.added() != 0;
+
+		summary
+	}
+}
+
+impl<N: Predecessors> Predecessors for Counter<'_, N> {
+	fn predecessors(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+		self.view.predecessors(id)
+	}
+}
+
+impl<N: Successors> Successors for Counter<'_, N> {
+	fn successors(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+		self.view.successors(id)
+	}
+}
+
+impl<N: View> View for Counter<'_, N> {
+	fn has_assignment(&self, id: usize, flag: Flag) -> bool {
+		self.view.has_assignment(id, flag)
+	}
+
+	fn add_no_operation(&mut self) -> usize {
+		self.summary.no_operations += 1;
+
+		self.view.add_no_operation()
+	}
+
+	fn add_selection(&mut self, flag: Flag) -> usize {
+		self.summary.selections += 1;
+
+		self.view.add_selection(flag)
+	}
+
+	fn add_assignment(&mut self, flag: Flag, value: usize) -> usize {
+		self.summary.assignments += 1;
+
+		self.view.add_assignment(flag, value)
+	}
+
+	fn add_edge(&mut self, from: usize, to: usize) {
+		self.view.add_edge(from, to);
+	}
+
+	fn replace_edge(&mut self, from: usize, to: usize, new: usize) {
+		self.view.replace_edge(from, to, new);
+	}
+
+	fn cost(&self, id: usize) -> usize {
+		self.view.cost(id)
+	}
+
+	fn can_duplicate(&self, id: usize) -> bool {
+		self.view.can_duplicate(id)
+	}
+
+	fn duplicate(&mut self, id: usize) -> usize {
+		self.summary.duplicates += 1;
+
+		self.view.duplicate(id)
+	}
+}
@@ -0,0 +1,216 @@
+use crate::{branch::Junction, repeat::Repetition, set::Set, view::Successors};
+
+/// A node of the structured control tree.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Node {
+	/// A single node of the graph.
+	Simple(usize),
+
+	/// A list of structures executed one after the other.
+	Sequence(Vec<Node>),
+
+	/// A head selecting one of the arms, which all meet again at the continuation
+	/// if there is one.
+	Branch {
+		head: usize,
+		arms: Vec<Node>,
+		continuation: Option<usize>,
+	},
+
+	/// A body repeated from the start until the latch, which leaves to the end
+	/// if there is one.
+	Loop {
+		start: usize,
+		latch: usize,
+		end: Option<usize>,
+		body: Box<Node>,
+	},
+}
+
+/// This structure builds a structured control tree out of a graph that was
+/// restructured by both [`Repeat`] and [`Branch`], using what they recorded.
+///
+/// [`Repeat`]: crate::repeat::Repeat
+/// [`Branch`]: crate::branch::Branch
+pub struct Builder {
+	repetitions: Vec<Repetition>,
+	junctions: Vec<Junction>,
+	visited: Set,
+}
+
+impl Builder {
+	/// Creates a new instance of the builder.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			repetitions: Vec::new(),
+			junctions: Vec::new(),
+			visited: Set::new(),
+		}
+	}
+
+	fn find_repetition(&self, id: usize) -> Option<Repetition> {
+		self.repetitions
+			.binary_search_by_key(&id, |repetition| repetition.start)
+			.ok()
+			.map(|index| self.repetitions[index])
+	}
+
+	fn find_junction(&self, id: usize) -> Option<Junction> {
+		self.junctions
+			.binary_search_by_key(&id, |junction| junction.head)
+			.ok()
+			.map(|index| self.junctions[index])
+	}
+
+	fn build_loop<N: Successors>(&mut self, view: &N, repetition: Repetition) -> Node {
+		let start = repetition.start;
+		let (body, latch) = self.build_sequence(view, start, None, Some(start), true);
+		// Repetitions that were only given a start, or that never end, have no end.
+		let latch = latch.or(repetition.latch).unwrap_or(start);
+		let end = view
+			.successors(latch)
+			.find(|&id| id != start)
+			.or(repetition.end);
+
+		Node::Loop {
+			start,
+			latch,
+			end,
+			body: Box::new(body),
+		}
+	}
+
+	fn build_branch<N: Successors>(
+		&mut self,
+		view: &N,
+		head: usize,
+		continuation: Option<usize>,
+		outer: Option<usize>,
+		start: Option<usize>,
+	) -> Node {
+		// Arms that never meet go on to wherever the enclosing sequence would have.
+		let last = continuation.or(outer);
+		let arms = view
+			.successors(head)
+			.map(|id| self.build_sequence(view, id, last, start, false).0)
+			.collect();
+
+		Node::Branch {
+			head,
+			arms,
+			continuation,
+		}
+	}
+
+	// The latch of the innermost repetition is the node that goes back to its
+	// start, which may differ from what `Repeat` recorded if nested repetitions
+	// were structured after it. Nodes that were already visited end the sequence,
+	// so that graphs which were not fully structured can not make us loop forever.
+	fn build_sequence<N: Successors>(
+		&mut self,
+		view: &N,
+		mut id: usize,
+		continuation: Option<usize>,
+		start: Option<usize>,
+		mut entered: bool,
+	) -> (Node, Option<usize>) {
+		let mut list = Vec::new();
+		let mut latch = None;
+
+		loop {
+			if Some(id) == continuation || self.visited.contains(id) {
+				break;
+			}
+
+			if !entered {
+				if let Some(repetition) = self.find_repetition(id) {
+					let repetition = self.build_loop(view, repetition);
+					let end = match repetition {
+						Node::Loop { end, .. } => end,
+						_ => None,
+					};
+
+					list.push(repetition);
+
+					let Some(end) = end else {
+						break;
+					};
+
+					id = end;
+
+					continue;
+				}
+			}
+
+			entered = false;
+
+			self.visited.grow_insert(id);
+
+			if start.is_some_and(|start| view.successors(id).any(|id| id == start)) {
+				list.push(Node::Simple(id));
+
+				latch = Some(id);
+
+				break;
+			}
+
+			if let Some(junction) = self.find_junction(id) {
+				list.push(self.build_branch(view, id, junction.continuation, continuation, start));
+
+				let Some(continuation) = junction.continuation else {
+					break;
+				};
+
+				id = continuation;
+
+				continue;
+			}
+
+			list.push(Node::Simple(id));
+
+			let mut successors = view.successors(id);
+
+			if let (Some(successor), None) = (successors.next(), successors.next()) {
+				id = successor;
+			} else {
+				break;
+			}
+		}
+
+		(Node::Sequence(list), latch)
+	}
+
+	/// Builds the structured control tree of the graph starting at the given node.
+	/// The repetitions and junctions are those recorded by [`Repeat`] and [`Branch`].
+	///
+	/// [`Repeat`]: crate::repeat::Repeat
+	/// [`Branch`]: crate::branch::Branch
+	pub fn run<N: Successors>(
+		&mut self,
+		view: &N,
+		start: usize,
+		repetitions: &[Repetition],
+		junctions: &[Junction],
+	) -> Node {
+		self.repetitions.clear();
+		self.repetitions.extend_from_slice(repetitions);
+		self.repetitions
+			.sort_unstable_by_key(|repetition| repetition.start);
+
+		self.junctions.clear();
+		self.junctions.extend_from_slice(junctions);
+		self.junctions
+			.sort_unstable_by_key(|junction| junction.head);
+
+		self.visited.clear();
+
+		self.build_sequence(view, start, None, None, false).0
+	}
+}
+
+impl Default for Builder {
+	fn default() -> Self {
+		Self::new()
+	}
+}
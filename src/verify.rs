@@ -0,0 +1,525 @@
+use std::ops::ControlFlow;
+
+use crate::{
+	pass::{
+		depth_first_searcher::DepthFirstSearcher, inverted::Inverted,
+		strongly_connected_finder::StronglyConnectedFinder,
+	},
+	set::{Set, Slice},
+	view::{Predecessors, Successors},
+};
+
+/// A description of the first region found not to be structured.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Violation {
+	/// A repetition does not have exactly one entry.
+	Entries {
+		region: Vec<usize>,
+		entries: Vec<usize>,
+	},
+
+	/// A repetition has many exits.
+	Exits {
+		region: Vec<usize>,
+		exits: Vec<usize>,
+	},
+
+	/// A repetition does not have exactly one latch going to both its start and its exit,
+	/// or only to its start when it has no exit.
+	Latches { region: Vec<usize>, start: usize },
+
+	/// A repetition has a latch going to nodes other than its start and its exit.
+	Latch {
+		region: Vec<usize>,
+		latch: usize,
+		successors: Vec<usize>,
+	},
+
+	/// A branch has arms that never meet again.
+	Unmerged { head: usize },
+
+	/// A branch has an arm that is entered from outside of it.
+	Crossing { head: usize, node: usize },
+}
+
+impl std::fmt::Display for Violation {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Entries { region, entries } => {
+				write!(f, "repetition {region:?} has entries {entries:?}")
+			}
+			Self::Exits { region, exits } => write!(f, "repetition {region:?} has exits {exits:?}"),
+			Self::Latches { region, start } => {
+				write!(
+					f,
+					"repetition {region:?} has no single latch for start {start}"
+				)
+			}
+			Self::Latch {
+				region,
+				latch,
+				successors,
+			} => {
+				write!(
+					f,
+					"repetition {region:?} has latch {latch} going to {successors:?}"
+				)
+			}
+			Self::Unmerged { head } => write!(f, "branch {head} has arms that never meet"),
+			Self::Crossing { head, node } => {
+				write!(
+					f,
+					"branch {head} has node {node} entered from outside its arm"
+				)
+			}
+		}
+	}
+}
+
+impl std::error::Error for Violation {}
+
+// A view of the graph without the edges going from latches back to their start.
+struct Acyclic<'a, N> {
+	view: &'a N,
+	repetitions: &'a [(usize, usize)],
+}
+
+impl<N> Acyclic<'_, N> {
+	fn is_forward(&self, from: usize, to: usize) -> bool {
+		self.repetitions.binary_search(&(from, to)).is_err()
+	}
+}
+
+impl<N: Predecessors> Predecessors for Acyclic<'_, N> {
+	fn predecessors(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+		self.view
+			.predecessors(id)
+			.filter(move |&predecessor| self.is_forward(predecessor, id))
+	}
+}
+
+impl<N: Successors> Successors for Acyclic<'_, N> {
+	fn successors(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
+		self.view
+			.successors(id)
+			.filter(move |&successor| self.is_forward(id, successor))
+	}
+}
+
+/// This structure verifies that a set of nodes is structured, as it should be after
+/// running [`Repeat`] and then [`Branch`] on it. Every strongly connected component
+/// must have one entry, at most one exit, and one latch going only to both its start
+/// and its exit if it has one, recursively. Every branch head must have a single continuation
+/// that its arms meet on, with no arm entered from outside of it. Arms that never reach
+/// the end of the set need not meet the others, and if none of them does there is no
+/// continuation.
+///
+/// Each branch head is checked on its own, searching the set once from every one of its
+/// arms, so verifying takes time proportional to the arms of all heads times the edges
+/// of the set. This is quadratic in the worst case, and it is meant for testing rather
+/// than for running after every restructuring.
+///
+/// [`Repeat`]: crate::repeat::Repeat
+/// [`Branch`]: crate::branch::Branch
+pub struct Verifier {
+	found: Vec<Set>,
+	repetitions: Vec<(usize, usize)>,
+
+	entries: Vec<usize>,
+	exits: Vec<usize>,
+	successors: Vec<usize>,
+	reached: Vec<(usize, usize)>,
+	arms: Vec<usize>,
+	tail: Set,
+	terminating: Set,
+
+	depth_first_searcher: DepthFirstSearcher,
+	strongly_connected_finder: StronglyConnectedFinder,
+}
+
+impl Verifier {
+	/// Creates a new instance of the verifier.
+	#[must_use]
+	pub const fn new() -> Self {
+		Self {
+			found: Vec::new(),
+			repetitions: Vec::new(),
+
+			entries: Vec::new(),
+			exits: Vec::new(),
+			successors: Vec::new(),
+			reached: Vec::new(),
+			arms: Vec::new(),
+			tail: Set::new(),
+			terminating: Set::new(),
+
+			depth_first_searcher: DepthFirstSearcher::new(),
+			strongly_connected_finder: StronglyConnectedFinder::new(),
+		}
+	}
+
+	fn find_strongly_connected<N: Predecessors + Successors>(&mut self, view: &N, set: Slice) {
+		self.strongly_connected_finder.run(view, set, |list| {
+			let repeats = if let &[first] = list {
+				view.successors(first).any(|id| id == first)
+			} else {
+				!list.is_empty()
+			};
+
+			if repeats {
+				self.found.push(list.iter().copied().collect());
+			}
+		});
+	}
+
+	fn find_entries_and_exits<N>(&mut self, view: &N, set: Slice, start: usize)
+	where
+		N: Predecessors + Successors,
+	{
+		self.entries.clear();
+		self.exits.clear();
+
+		for id in set {
+			if id == start || view.predecessors(id).any(|id| !set.contains(id)) {
+				self.entries.push(id);
+			}
+
+			self.exits
+				.extend(view.successors(id).filter(|&id| !set.contains(id)));
+		}
+
+		self.exits.sort_unstable();
+		self.exits.dedup();
+	}
+
+	fn find_one_latch<N>(view: &N, set: Slice, start: usize, end: Option<usize>) -> Option<usize>
+	where
+		N: Predecessors + Successors,
+	{
+		let mut repetitions = view.predecessors(start).filter(|&id| set.contains(id));
+
+		let (Some(repetition), None) = (repetitions.next(), repetitions.next()) else {
+			return None;
+		};
+
+		let Some(end) = end else {
+			return view
+				.successors(repetition)
+				.all(|id| id == start)
+				.then_some(repetition);
+		};
+
+		let mut exits = view.predecessors(end).filter(|&id| set.contains(id));
+
+		match (exits.next(), exits.next()) {
+			(Some(exit), None) if repetition == exit => Some(repetition),
+			_ => None,
+		}
+	}
+
+	fn check_repetition<N>(&mut self, view: &N, set: &Set, start: usize) -> Result<usize, Violation>
+	where
+		N: Predecessors + Successors,
+	{
+		let region = || set.ascending().collect();
+
+		self.find_entries_and_exits(view, set.as_slice(), start);
+
+		let &[entry] = self.entries.as_slice() else {
+			return Err(Violation::Entries {
+				region: region(),
+				entries: self.entries.clone(),
+			});
+		};
+
+		let exit = match *self.exits.as_slice() {
+			[] => None,
+			[exit] => Some(exit),
+			_ => {
+				return Err(Violation::Exits {
+					region: region(),
+					exits: self.exits.clone(),
+				})
+			}
+		};
+
+		let Some(latch) = Self::find_one_latch(view, set.as_slice(), entry, exit) else {
+			return Err(Violation::Latches {
+				region: region(),
+				start: entry,
+			});
+		};
+
+		if view
+			.successors(latch)
+			.any(|id| id != entry && Some(id) != exit)
+		{
+			return Err(Violation::Latch {
+				region: region(),
+				latch,
+				successors: view.successors(latch).collect(),
+			});
+		}
+
+		self.repetitions.push((latch, entry));
+
+		Ok(entry)
+	}
+
+	fn check_repetitions<N>(&mut self, view: &N, set: Slice, start: usize) -> Result<(), Violation>
+	where
+		N: Predecessors + Successors,
+	{
+		self.repetitions.clear();
+		self.found.clear();
+		self.find_strongly_connected(view, set);
+
+		while let Some(mut child) = self.found.pop() {
+			let entry = self.check_repetition(view, &child, start)?;
+
+			child.remove(entry);
+
+			self.find_strongly_connected(view, child.as_slice());
+		}
+
+		self.repetitions.sort_unstable();
+
+		Ok(())
+	}
+
+	// Nodes that can reach one without successors in the set are those that may leave it.
+	// They are searched backwards from every such node, with the nodes left to search only
+	// set once, so that those already found from an earlier one are not searched again.
+	fn find_terminating<N: Predecessors + Successors>(&mut self, view: &N, set: Slice) {
+		self.terminating.clear();
+		self.depth_first_searcher.nodes_mut().clone_from_slice(set);
+
+		for id in set {
+			if view.successors(id).any(|id| set.contains(id)) {
+				continue;
+			}
+
+			self.depth_first_searcher
+				.run(&Inverted(view), id, |id, post| {
+					if post {
+						self.terminating.grow_insert(id);
+					}
+				});
+		}
+	}
+
+	fn find_continuation<N: Successors>(
+		&mut self,
+		view: &N,
+		set: Slice,
+		head: usize,
+	) -> Option<usize> {
+		self.depth_first_searcher.nodes_mut().clone_from_slice(set);
+		self.depth_first_searcher.clear_numbers();
+
+		let _ = self
+			.depth_first_searcher
+			.run_classified(view, head, |_| ControlFlow::<()>::Continue(()));
+
+		// The continuation is the first node, in reverse post-order, reached by every arm that
+		// may leave the set and by the most arms otherwise. Arms that repeat forever may not
+		// reach it, but at least two arms must.
+		let reached = &mut self.reached;
+		let terminating = &self.terminating;
+
+		reached.clear();
+		reached.resize(
+			self.depth_first_searcher
+				.reverse_post_order()
+				.max()
+				.map_or(0, |id| id + 1),
+			(0, 0),
+		);
+
+		for &successor in &self.successors {
+			let is_terminating = terminating.contains(successor);
+
+			self.depth_first_searcher.nodes_mut().clone_from_slice(set);
+			self.depth_first_searcher.run(view, successor, |id, post| {
+				if post {
+					reached[id].0 += usize::from(is_terminating);
+					reached[id].1 += 1;
+				}
+			});
+		}
+
+		let all = self
+			.successors
+			.iter()
+			.filter(|&&id| terminating.contains(id))
+			.count();
+
+		let most = self
+			.reached
+			.iter()
+			.filter(|reached| reached.0 == all)
+			.map(|reached| reached.1)
+			.max()
+			.filter(|&most| most > 1)?;
+
+		self.depth_first_searcher
+			.reverse_post_order()
+			.find(|&id| self.reached[id] == (all, most))
+	}
+
+	fn mark_arms<N: Successors>(
+		&mut self,
+		view: &N,
+		set: Slice,
+		head: usize,
+	) -> Result<(), Violation> {
+		let arms = &mut self.arms;
+
+		arms.clear();
+
+		for (index, &successor) in self.successors.iter().enumerate() {
+			let nodes = self.depth_first_searcher.nodes_mut();
+
+			nodes.clone_from_slice(set);
+
+			for id in self.tail.ascending() {
+				nodes.remove(id);
+			}
+
+			let mut crossing = None;
+
+			self.depth_first_searcher.run(view, successor, |id, post| {
+				if post {
+					return;
+				}
+
+				if arms.len() <= id {
+					arms.resize(id + 1, usize::MAX);
+				}
+
+				if arms[id] != usize::MAX {
+					crossing = crossing.or(Some(id));
+				}
+
+				arms[id] = index;
+			});
+
+			if let Some(node) = crossing {
+				return Err(Violation::Crossing { head, node });
+			}
+		}
+
+		Ok(())
+	}
+
+	fn check_arms<N: Predecessors>(&self, view: &N, head: usize) -> Result<(), Violation> {
+		for (node, &arm) in self.arms.iter().enumerate() {
+			if arm == usize::MAX {
+				continue;
+			}
+
+			let is_outside = |id: usize| id != head && self.arms.get(id) != Some(&arm);
+
+			if view.predecessors(node).any(is_outside) {
+				return Err(Violation::Crossing { head, node });
+			}
+		}
+
+		Ok(())
+	}
+
+	fn check_branch<N>(&mut self, view: &N, set: Slice, head: usize) -> Result<(), Violation>
+	where
+		N: Predecessors + Successors,
+	{
+		self.successors.clear();
+		self.successors
+			.extend(view.successors(head).filter(|&id| set.contains(id)));
+
+		if self.successors.len() < 2 {
+			return Ok(());
+		}
+
+		self.tail.clear();
+
+		// Arms that never meet can only be checked for not crossing each other, as
+		// they may go on to the continuation of a branch enclosing them. Only one of
+		// them may leave the set, as the others must repeat forever.
+		let Some(continuation) = self.find_continuation(view, set, head) else {
+			let terminating = &self.terminating;
+
+			if self
+				.successors
+				.iter()
+				.filter(|&&id| terminating.contains(id))
+				.count() > 1
+			{
+				return Err(Violation::Unmerged { head });
+			}
+
+			return self.mark_arms(view, set, head);
+		};
+
+		self.depth_first_searcher.nodes_mut().clone_from_slice(set);
+		self.depth_first_searcher
+			.run(view, continuation, |id, post| {
+				if post {
+					self.tail.grow_insert(id);
+				}
+			});
+
+		self.mark_arms(view, set, head)?;
+		self.check_arms(view, head)
+	}
+
+	/// Verifies that the repetitions of the given set of nodes, entered at the start node,
+	/// are structured, without looking at its branches.
+	///
+	/// # Errors
+	///
+	/// Returns a description of the first repetition found not to be structured.
+	pub fn run_repetitions<N>(
+		&mut self,
+		view: &N,
+		set: Slice,
+		start: usize,
+	) -> Result<(), Violation>
+	where
+		N: Predecessors + Successors,
+	{
+		self.check_repetitions(view, set, start)
+	}
+
+	/// Verifies that the given set of nodes, entered at the start node, is structured.
+	///
+	/// # Errors
+	///
+	/// Returns a description of the first region found not to be structured.
+	pub fn run<N>(&mut self, view: &N, set: Slice, start: usize) -> Result<(), Violation>
+	where
+		N: Predecessors + Successors,
+	{
+		self.check_repetitions(view, set, start)?;
+		self.find_terminating(view, set);
+
+		let repetitions = std::mem::take(&mut self.repetitions);
+		let acyclic = Acyclic {
+			view,
+			repetitions: &repetitions,
+		};
+
+		let result = set
+			.into_iter()
+			.try_for_each(|head| self.check_branch(&acyclic, set, head));
+
+		self.repetitions = repetitions;
+
+		result
+	}
+}
+
+impl Default for Verifier {
+	fn default() -> Self {
+		Self::new()
+	}
+}
@@ -26,6 +26,41 @@ pub enum Flag {
 	C,
 }
 
+/// The kind of a node in a control flow graph.
+#[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
+pub enum Kind {
+	/// A node of the original graph.
+	Original,
+
+	/// A synthetic node that does nothing.
+	NoOperation,
+
+	/// A synthetic node that selects the successor at the index held by the flag.
+	Selection(Flag),
+
+	/// A synthetic node that assigns a value to the flag.
+	Assignment(Flag, usize),
+}
+
+pub trait Classify {
+	fn kind(&self, id: usize) -> Kind;
+
+	/// Returns the node this node was duplicated from, or itself if it was not.
+	fn origin(&self, id: usize) -> usize {
+		id
+	}
+}
+
+impl<T: Classify> Classify for &T {
+	fn kind(&self, id: usize) -> Kind {
+		(**self).kind(id)
+	}
+
+	fn origin(&self, id: usize) -> usize {
+		(**self).origin(id)
+	}
+}
+
 /// A view into a control flow graph.
 pub trait View: Predecessors + Successors {
 	/// Returns whether the node has an assignment to a flag.
@@ -45,4 +80,19 @@ pub trait View: Predecessors + Successors {
 
 	/// Replaces the edge from the `from` node to the `to` node with an edge to the `new` node.
 	fn replace_edge(&mut self, from: usize, to: usize, new: usize);
+
+	/// Returns the cost of duplicating the node, such as its size in instructions.
+	fn cost(&self, _id: usize) -> usize {
+		1
+	}
+
+	/// Returns whether the node can be copied. Graphs that can not copy nodes
+	/// return `false`, so that flags are used instead.
+	fn can_duplicate(&self, _id: usize) -> bool {
+		false
+	}
+
+	/// Adds a copy of the node, without any edges, to the graph and returns its index.
+	/// It is only called on nodes for which [`View::can_duplicate`] returns `true`.
+	fn duplicate(&mut self, id: usize) -> usize;
 }